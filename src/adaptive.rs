@@ -0,0 +1,120 @@
+use std::{collections::VecDeque, time::Duration};
+
+// How far back captures and leaks count towards the success rate
+const WINDOW: Duration = Duration::from_secs(60);
+// How strongly the spawn rate reacts to being off target
+const GAIN: f32 = 2.0;
+// Seconds it takes the spawn rate to settle on a new level
+const SETTLE_TIME: f32 = 5.0;
+const MIN_RATE: f32 = 10.0;
+
+// Keeps the spawn rate near the player's measured speed, raising it while more
+// words than the target are typed and lowering it while too many leak
+pub(crate) struct AdaptiveController {
+    target: f32,
+    rate: f32,
+    elapsed: Duration,
+    // When each word was typed (true) or leaked (false)
+    outcomes: VecDeque<(Duration, bool)>,
+}
+
+impl AdaptiveController {
+    pub(crate) fn new(target: f32, start_rate: f32) -> Self {
+        AdaptiveController {
+            target: target.clamp(0.0, 1.0),
+            rate: start_rate.max(MIN_RATE),
+            elapsed: Duration::ZERO,
+            outcomes: VecDeque::new(),
+        }
+    }
+
+    pub(crate) fn record(&mut self, typed: bool) {
+        self.outcomes.push_back((self.elapsed, typed));
+    }
+
+    pub(crate) fn update(&mut self, dt: Duration, measured_wpm: f32) {
+        self.elapsed += dt;
+        while let Some((time, _)) = self.outcomes.front() {
+            if self.elapsed - *time <= WINDOW {
+                break;
+            }
+            self.outcomes.pop_front();
+        }
+
+        let base = if measured_wpm > 0.0 {
+            measured_wpm
+        } else {
+            self.rate
+        };
+        let success = self.success_rate().unwrap_or(self.target);
+        let desired = (base * (1.0 + GAIN * (success - self.target))).max(MIN_RATE);
+        let settle = (dt.as_secs_f32() / SETTLE_TIME).min(1.0);
+        self.rate += (desired - self.rate) * settle;
+    }
+
+    // Words spawned per minute
+    pub(crate) fn rate(&self) -> f32 {
+        self.rate
+    }
+
+    pub(crate) fn target(&self) -> f32 {
+        self.target
+    }
+
+    // Share of recent words that were typed before they leaked
+    pub(crate) fn success_rate(&self) -> Option<f32> {
+        if self.outcomes.is_empty() {
+            return None;
+        }
+        let typed = self.outcomes.iter().filter(|(_, typed)| *typed).count();
+        Some(typed as f32 / self.outcomes.len() as f32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(controller: &mut AdaptiveController, seconds: u64, wpm: f32, typed: Option<bool>) {
+        for _ in 0..seconds {
+            if let Some(typed) = typed {
+                controller.record(typed);
+            }
+            controller.update(Duration::from_secs(1), wpm);
+        }
+    }
+
+    #[test]
+    fn follows_the_measured_speed_on_target() {
+        let mut controller = AdaptiveController::new(0.9, 30.0);
+        for i in 0..60 {
+            controller.record(i % 10 != 0);
+            controller.update(Duration::from_secs(1), 50.0);
+        }
+        assert!((controller.rate() - 50.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn speeds_up_when_nothing_leaks() {
+        let mut controller = AdaptiveController::new(0.9, 30.0);
+        run(&mut controller, 60, 40.0, Some(true));
+        assert_eq!(controller.success_rate(), Some(1.0));
+        assert!(controller.rate() > 40.0);
+    }
+
+    #[test]
+    fn slows_down_when_words_leak() {
+        let mut controller = AdaptiveController::new(0.9, 30.0);
+        run(&mut controller, 60, 40.0, Some(false));
+        assert!(controller.rate() < 30.0);
+        assert!(controller.rate() >= MIN_RATE);
+    }
+
+    #[test]
+    fn keeps_the_start_rate_until_there_is_data() {
+        let mut controller = AdaptiveController::new(0.9, 30.0);
+        run(&mut controller, 10, 0.0, None);
+        assert_eq!(controller.rate(), 30.0);
+        assert_eq!(controller.success_rate(), None);
+    }
+}
@@ -0,0 +1,118 @@
+use std::path::PathBuf;
+
+use clap::{builder::TypedValueParser, Parser, ValueEnum};
+
+use crate::{
+    difficulty::Difficulty,
+    language_pack::{self, LanguagePack},
+    mode::{InputMode, Mode},
+};
+
+#[derive(Clone, Copy, ValueEnum)]
+pub(crate) enum ModeKind {
+    Classic,
+    TimeAttack,
+    WordCount,
+    Lives,
+    Zen,
+    Practice,
+}
+
+#[derive(Parser)]
+#[command(version, about)]
+pub(crate) struct Args {
+    /// Language pack of the words to type, by name or directory name
+    #[arg(short, long, default_value = "english", value_parser = language_pack::find)]
+    pub(crate) language: LanguagePack,
+
+    /// Word list file to play with, one word per line (can be repeated)
+    #[arg(short, long, value_name = "FILE")]
+    pub(crate) words: Vec<PathBuf>,
+
+    /// Type sentences with their capitals and punctuation: the language's own, or one per line of the --words files
+    #[arg(long)]
+    pub(crate) sentences: bool,
+
+    /// Game mode to play
+    #[arg(short, long, value_enum, default_value_t = ModeKind::Classic)]
+    pub(crate) mode: ModeKind,
+
+    /// Length of a time attack game in seconds
+    #[arg(
+        long,
+        default_value_t = 60,
+        value_parser = clap::builder::PossibleValuesParser::new(["60", "120", "300"])
+            .map(|s| s.parse::<u32>().unwrap()),
+    )]
+    pub(crate) duration: u32,
+
+    /// Number of words to type in a word count game
+    #[arg(long, default_value_t = 25, value_parser = clap::value_parser!(u32).range(1..))]
+    pub(crate) word_count: u32,
+
+    /// Number of words that may reach the edge in a lives game
+    #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..))]
+    pub(crate) lives: u32,
+
+    /// Words typed in a row without mistakes or leaks that restore a life in a lives game (0 to turn off)
+    #[arg(long, default_value_t = 0)]
+    pub(crate) regen: u32,
+
+    /// How fast words spawn and move; custom uses the curve from the config file
+    #[arg(short, long, value_enum, default_value_t = Difficulty::Normal)]
+    pub(crate) difficulty: Difficulty,
+
+    /// How keys are typed: whole words, or locking on to a word and shooting its letters off
+    #[arg(short, long, value_enum, default_value_t = InputMode::Words)]
+    pub(crate) input: InputMode,
+
+    /// Match Hangul by the keys that type it (jamo), for keyboards without an IME
+    #[arg(long)]
+    pub(crate) jamo: bool,
+
+    /// Keep the capitals of the word list and match them exactly
+    #[arg(long)]
+    pub(crate) strict: bool,
+
+    /// Capitalise some words and end some with punctuation, matched exactly (implies --strict)
+    #[arg(long)]
+    pub(crate) punctuate: bool,
+
+    /// Seed for the word order, rows and speed changes, to replay the same game
+    #[arg(short, long)]
+    pub(crate) seed: Option<u64>,
+
+    /// Name to record in the high-score table
+    #[arg(short, long, default_value_t = default_name())]
+    pub(crate) name: String,
+
+    /// Skip the menus and start playing straight away
+    #[arg(long)]
+    pub(crate) no_menu: bool,
+}
+
+impl Args {
+    pub(crate) fn mode(&self) -> Mode {
+        match self.mode {
+            ModeKind::Classic => Mode::Classic,
+            ModeKind::TimeAttack => Mode::TimeAttack {
+                seconds: self.duration,
+            },
+            ModeKind::WordCount => Mode::WordCount {
+                words: self.word_count,
+            },
+            ModeKind::Lives => Mode::Lives {
+                lives: self.lives,
+                regen: self.regen,
+            },
+            ModeKind::Zen => Mode::Zen,
+            ModeKind::Practice => Mode::Practice,
+        }
+    }
+}
+
+fn default_name() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "Player".to_string())
+}
@@ -0,0 +1,73 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::{difficulty::DifficultyCurve, storage, GameError};
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub(crate) struct Config {
+    pub(crate) custom_difficulty: Option<DifficultyCurve>,
+    // Why the config file could not be loaded
+    #[serde(skip)]
+    pub(crate) error: Option<String>,
+}
+
+impl Config {
+    // Load the config file, falling back to the defaults if it is missing or broken
+    pub(crate) fn load() -> Self {
+        let Some(path) = path() else {
+            return Config::default();
+        };
+        Self::read(&path).unwrap_or_else(|err| Config {
+            error: Some(err.to_string()),
+            ..Config::default()
+        })
+    }
+
+    fn read(path: &Path) -> Result<Self, GameError> {
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+        let data = fs::read_to_string(path).map_err(|err| {
+            GameError(format!(
+                "Could not read config from '{}': {}",
+                path.display(),
+                err
+            ))
+        })?;
+        toml::from_str(&data).map_err(|err| {
+            GameError(format!(
+                "Config in '{}' is invalid: {}",
+                path.display(),
+                err
+            ))
+        })
+    }
+}
+
+pub(crate) fn path() -> Option<PathBuf> {
+    storage::config_path("config.toml")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn custom_curve_fills_in_missing_fields() {
+        let config: Config = toml::from_str(
+            "[custom_difficulty]\n\
+             spawn_rate = 45.0\n\
+             max_words = 5\n",
+        )
+        .unwrap();
+        let curve = config.custom_difficulty.unwrap();
+        assert_eq!(curve.spawn_rate, 45.0);
+        assert_eq!(curve.max_words, 5);
+        assert_eq!(curve.speed_jitter, DifficultyCurve::default().speed_jitter);
+    }
+}
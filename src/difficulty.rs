@@ -0,0 +1,180 @@
+use std::time::Duration;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use strum::Display;
+
+// Slowest spawn rate, in words per minute, so that a custom curve with a rate of
+// zero or ramps that go below it still spawns words
+const MIN_SPAWN_RATE: f32 = 1.0;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Display, ValueEnum, Serialize, Deserialize)]
+pub(crate) enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    Insane,
+    // Follows the player's measured speed
+    Adaptive,
+    // The curve from the config file
+    Custom,
+}
+
+impl Difficulty {
+    // The presets offered on the home screen, with custom only if there is a curve for it
+    pub(crate) fn options(custom: Option<&DifficultyCurve>) -> Vec<Difficulty> {
+        let mut options = vec![
+            Difficulty::Easy,
+            Difficulty::Normal,
+            Difficulty::Hard,
+            Difficulty::Insane,
+            Difficulty::Adaptive,
+        ];
+        if custom.is_some() {
+            options.push(Difficulty::Custom);
+        }
+        options
+    }
+
+    // Custom falls back to normal when the config file has no curve
+    pub(crate) fn curve(&self, custom: Option<&DifficultyCurve>) -> DifficultyCurve {
+        match self {
+            Difficulty::Easy => DifficultyCurve {
+                spawn_rate: 20.0,
+                ramp_per_point: 0.05,
+                ramp_per_minute: 0.0,
+                max_words: 6,
+                speed_jitter: 0.6,
+                max_speed: 4.0,
+                ..DifficultyCurve::default()
+            },
+            Difficulty::Normal => DifficultyCurve::default(),
+            Difficulty::Hard => DifficultyCurve {
+                spawn_rate: 40.0,
+                ramp_per_point: 0.15,
+                ramp_per_minute: 5.0,
+                max_words: 20,
+                speed_jitter: 1.6,
+                max_speed: 12.0,
+                ..DifficultyCurve::default()
+            },
+            Difficulty::Insane => DifficultyCurve {
+                spawn_rate: 60.0,
+                ramp_per_point: 0.2,
+                ramp_per_minute: 10.0,
+                max_words: 40,
+                speed_jitter: 2.0,
+                max_speed: 20.0,
+                ..DifficultyCurve::default()
+            },
+            Difficulty::Adaptive => DifficultyCurve {
+                max_words: 20,
+                max_speed: 12.0,
+                target_success: Some(0.9),
+                ..DifficultyCurve::default()
+            },
+            Difficulty::Custom => custom.copied().unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct DifficultyCurve {
+    // Words spawned per minute at the start of a game
+    pub(crate) spawn_rate: f32,
+    // Extra words per minute for every point scored
+    pub(crate) ramp_per_point: f32,
+    // Extra words per minute for every minute played
+    pub(crate) ramp_per_minute: f32,
+    // Most words on the screen at once
+    pub(crate) max_words: usize,
+    // Largest random change to a word's speed, in percent of the screen per second
+    pub(crate) speed_jitter: f32,
+    // Fastest a word can move, in percent of the screen per second
+    pub(crate) max_speed: f32,
+    // Share of words the player should type before they leak. When set, the spawn
+    // rate follows the player's speed instead of the ramps.
+    pub(crate) target_success: Option<f32>,
+}
+
+impl Default for DifficultyCurve {
+    fn default() -> Self {
+        DifficultyCurve {
+            spawn_rate: 30.0,
+            ramp_per_point: 0.1,
+            ramp_per_minute: 0.0,
+            max_words: 12,
+            speed_jitter: 1.2,
+            max_speed: 8.0,
+            target_success: None,
+        }
+    }
+}
+
+impl DifficultyCurve {
+    // Words spawned per minute after scoring a number of points
+    pub(crate) fn spawn_rate(&self, score: f32, elapsed: Duration) -> f32 {
+        (self.spawn_rate
+            + self.ramp_per_point * score
+            + self.ramp_per_minute * elapsed.as_secs_f32() / 60.0)
+            .max(MIN_SPAWN_RATE)
+    }
+
+    // Word speed for a spawn rate plus some jitter. Words get faster as they spawn faster.
+    pub(crate) fn speed(&self, spawn_rate: f32, jitter: f32) -> f32 {
+        (spawn_rate / 20.0 + jitter).clamp(0.6, self.max_speed.max(0.6))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_get_harder() {
+        let curves: Vec<DifficultyCurve> = Difficulty::options(None)
+            .iter()
+            .map(|d| d.curve(None))
+            .filter(|c| c.target_success.is_none())
+            .collect();
+        for pair in curves.windows(2) {
+            assert!(pair[1].spawn_rate > pair[0].spawn_rate);
+            assert!(pair[1].max_speed > pair[0].max_speed);
+        }
+    }
+
+    #[test]
+    fn speed_is_capped() {
+        let curve = Difficulty::Easy.curve(None);
+        assert_eq!(curve.speed(1000.0, 0.0), curve.max_speed);
+        assert_eq!(curve.speed(0.0, -5.0), 0.6);
+    }
+
+    #[test]
+    fn spawn_rate_stays_positive() {
+        let curve = DifficultyCurve {
+            spawn_rate: 0.0,
+            ramp_per_minute: -10.0,
+            ..DifficultyCurve::default()
+        };
+        assert_eq!(curve.spawn_rate(0.0, Duration::ZERO), MIN_SPAWN_RATE);
+        assert_eq!(
+            curve.spawn_rate(0.0, Duration::from_secs(600)),
+            MIN_SPAWN_RATE
+        );
+    }
+
+    #[test]
+    fn custom_needs_a_curve() {
+        let custom = DifficultyCurve {
+            spawn_rate: 99.0,
+            ..DifficultyCurve::default()
+        };
+        assert!(!Difficulty::options(None).contains(&Difficulty::Custom));
+        assert!(Difficulty::options(Some(&custom)).contains(&Difficulty::Custom));
+        assert_eq!(Difficulty::Custom.curve(Some(&custom)).spawn_rate, 99.0);
+        assert_eq!(Difficulty::Custom.curve(None), DifficultyCurve::default());
+    }
+}
@@ -0,0 +1,1225 @@
+use std::time::Duration;
+
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::{
+    adaptive::AdaptiveController,
+    difficulty::DifficultyCurve,
+    key_stats::KeyStats,
+    matching::Matching,
+    mode::Mode,
+    practice::Focus,
+    scoring::{self, ScoreBreakdown},
+    stats::TypingStats,
+};
+
+pub(crate) const MIN_LANES: usize = 10;
+const FIRST_SPAWN: Duration = Duration::from_millis(333);
+// Streams of the seeded generator, so that lanes, words and speed jitter each
+// follow their own sequence. The words come in the same order whatever the
+// player does, and so do the lanes unless one is still taken when its turn comes
+// around again. Ramps per point make spawn times and speeds follow the score.
+const SPEED_STREAM: u64 = 0;
+const LANE_STREAM: u64 = 1;
+const WORD_STREAM: u64 = 2;
+// Words typed in a row that raise the score multiplier by one, up to the maximum
+const STREAK_STEP: u32 = 5;
+const MAX_MULTIPLIER: u32 = 5;
+// Chance of a word getting a capital, and of it getting punctuation, when punctuating
+const CAPITAL_CHANCE: f64 = 0.3;
+const PUNCTUATION_CHANCE: f64 = 0.3;
+const PUNCTUATION: [char; 4] = [',', '.', ';', '?'];
+
+#[derive(Debug, Clone)]
+pub(crate) struct Word {
+    // The word as it is in the list, before capitals and punctuation are added
+    source: String,
+    pub(crate) text: String,
+    // The text in the form that input is compared with
+    pub(crate) form: String,
+    pub(crate) y: usize,
+    x: f32,
+    // Percent of the screen width per second
+    speed: f32,
+    // Letters of the form shot off while locked on to the word, zero for every
+    // other word
+    pub(crate) shot: usize,
+}
+
+impl Word {
+    fn new(source: String, text: String, form: String, y: usize, speed: f32) -> Self {
+        Word {
+            source,
+            text,
+            form,
+            x: 0.0,
+            y,
+            speed,
+            shot: 0,
+        }
+    }
+
+    fn increment(&mut self, dt: Duration) {
+        self.x += self.speed * dt.as_secs_f32();
+    }
+
+    pub(crate) fn progress(&self) -> f32 {
+        self.x / 100.0
+    }
+}
+
+// The game rules, free of any terminal input or rendering
+pub(crate) struct Engine {
+    mode: Mode,
+    curve: DifficultyCurve,
+    matching: Matching,
+    // Give words random capitals and punctuation
+    punctuate: bool,
+    adaptive: Option<AdaptiveController>,
+    // Keys that words are picked for, in practice games
+    focus: Focus,
+    // Speeds, capitals and punctuation
+    rng: ChaCha8Rng,
+    lane_rng: ChaCha8Rng,
+    word_rng: ChaCha8Rng,
+    all_words: Vec<String>,
+    // Words in the order they spawn, a shuffle of all words that is used up from
+    // the back before it is shuffled again
+    word_pool: Vec<String>,
+    words: Vec<Word>,
+    word_slots: Vec<bool>,
+    // Lanes in the order they are used, a shuffle of all lanes that is used up
+    // from the back before it is shuffled again
+    lane_pool: Vec<usize>,
+    spawn_timer: Duration,
+    score: ScoreBreakdown,
+    difficulty: f32,
+    stats: TypingStats,
+    key_stats: KeyStats,
+    // The last correct key and when it was typed, to time bigrams
+    last_key: Option<(char, Duration)>,
+    leaked: usize,
+    health: u32,
+    streak: u32,
+    longest_streak: u32,
+    over: bool,
+}
+
+impl Engine {
+    pub(crate) fn new(
+        word_pool: Vec<String>,
+        seed: u64,
+        lanes: usize,
+        mode: Mode,
+        curve: DifficultyCurve,
+    ) -> Self {
+        Engine {
+            mode,
+            curve,
+            matching: Matching::default(),
+            punctuate: false,
+            adaptive: curve
+                .target_success
+                .map(|target| AdaptiveController::new(target, curve.spawn_rate)),
+            focus: Focus::default(),
+            rng: seeded_stream(seed, SPEED_STREAM),
+            lane_rng: seeded_stream(seed, LANE_STREAM),
+            word_rng: seeded_stream(seed, WORD_STREAM),
+            all_words: word_pool,
+            word_pool: vec![],
+            words: vec![],
+            word_slots: vec![false; lanes.max(MIN_LANES)],
+            lane_pool: vec![],
+            spawn_timer: FIRST_SPAWN,
+            score: ScoreBreakdown::default(),
+            difficulty: curve.spawn_rate(0.0, Duration::ZERO),
+            stats: TypingStats::default(),
+            key_stats: KeyStats::default(),
+            last_key: None,
+            leaked: 0,
+            health: match mode {
+                Mode::Lives { lives, .. } => lives,
+                _ => 0,
+            },
+            streak: 0,
+            longest_streak: 0,
+            over: false,
+        }
+    }
+
+    pub(crate) fn tick(&mut self, dt: Duration) {
+        if self.over {
+            return;
+        }
+        self.stats.tick(dt);
+        if let Some(adaptive) = &mut self.adaptive {
+            adaptive.update(dt, self.stats.rolling_net_wpm());
+        }
+
+        for word in self.words.iter_mut() {
+            word.increment(dt);
+        }
+        self.remove_leaked_words();
+        if self.over {
+            return;
+        }
+        if let Mode::TimeAttack { seconds } = self.mode {
+            if self.stats.elapsed() >= Duration::from_secs(seconds as u64) {
+                self.over = true;
+                return;
+            }
+        }
+
+        self.spawn_timer = self.spawn_timer.saturating_sub(dt);
+        if self.spawn_timer.is_zero() {
+            if self.words.len() < self.curve.max_words {
+                self.spawn_new_word();
+            }
+            self.difficulty = match &self.adaptive {
+                Some(adaptive) => adaptive.rate(),
+                None => self.curve.spawn_rate(self.score(), self.stats.elapsed()),
+            };
+            self.spawn_timer = Duration::from_secs_f32(60.0 / self.difficulty);
+        }
+    }
+
+    // Record a typed character and capture the word it completes, if any
+    pub(crate) fn on_input(&mut self, text: &str) -> bool {
+        let input = self.matching.form(text);
+        let correct = self.words.iter().any(|w| w.form.starts_with(&input));
+        self.stats.record_key(correct);
+        self.record_expected_key(&input, correct);
+        if !correct {
+            self.streak = 0;
+        }
+        self.check_if_typed(&input)
+    }
+
+    // Shoot letters off the locked word. A key can be more than one letter, like
+    // a Hangul syllable when matching jamo.
+    pub(crate) fn on_lock_key(&mut self, key: char) -> bool {
+        let mut captured = false;
+        for key in self.matching.form(&key.to_string()).chars() {
+            captured |= self.shoot(key);
+        }
+        captured
+    }
+
+    // Shoot the next letter off the locked word. Without a lock, the word nearest
+    // the edge that starts with the key is locked on to first.
+    fn shoot(&mut self, key: char) -> bool {
+        let locked = self.words.iter().position(|w| w.shot > 0);
+        let index = locked.or_else(|| self.nearest(|w| w.form.starts_with(key)));
+        let expected = index.and_then(|i| {
+            let word = &self.words[i];
+            word.form.chars().nth(word.shot)
+        });
+        let correct = expected == Some(key);
+        self.stats.record_key(correct);
+        self.record_key_against(key, expected, locked.is_none(), correct);
+        let Some(index) = index.filter(|_| correct) else {
+            self.streak = 0;
+            return false;
+        };
+
+        let word = &mut self.words[index];
+        word.shot += 1;
+        if word.shot < word.form.chars().count() {
+            return false;
+        }
+        let word = self.words.remove(index);
+        self.capture(&word);
+        true
+    }
+
+    // Let go of the locked word, which gets all its letters back
+    pub(crate) fn release_lock(&mut self) {
+        for word in self.words.iter_mut() {
+            word.shot = 0;
+        }
+        self.last_key = None;
+    }
+
+    pub(crate) fn locked(&self) -> Option<&Word> {
+        self.words.iter().find(|w| w.shot > 0)
+    }
+
+    // Change the number of lanes, moving words off removed lanes into free ones.
+    // Words that do not fit anymore go back into the pool.
+    pub(crate) fn resize(&mut self, lanes: usize) {
+        let lanes = lanes.max(MIN_LANES);
+        if lanes == self.word_slots.len() {
+            return;
+        }
+        self.word_slots = vec![false; lanes];
+        self.lane_pool.clear();
+        for word in self.words.iter().filter(|w| w.y < lanes) {
+            self.word_slots[word.y] = true;
+        }
+        let mut free_lanes: Vec<usize> = (0..lanes).filter(|&i| !self.word_slots[i]).collect();
+        for word in self.words.iter_mut().filter(|w| w.y >= lanes) {
+            if let Some(lane) = free_lanes.pop() {
+                word.y = lane;
+                self.word_slots[lane] = true;
+            }
+        }
+        let (words, evicted): (Vec<Word>, Vec<Word>) =
+            self.words.drain(..).partition(|w| w.y < lanes);
+        self.words = words;
+        self.word_pool.extend(evicted.into_iter().map(|w| w.source));
+    }
+
+    pub(crate) fn lanes(&self) -> usize {
+        self.word_slots.len()
+    }
+
+    pub(crate) fn on_backspace(&mut self) {
+        self.stats.record_backspace();
+        self.last_key = None;
+    }
+
+    pub(crate) fn words(&self) -> &[Word] {
+        &self.words
+    }
+
+    pub(crate) fn score(&self) -> f32 {
+        self.score.total()
+    }
+
+    pub(crate) fn score_breakdown(&self) -> &ScoreBreakdown {
+        &self.score
+    }
+
+    pub(crate) fn difficulty(&self) -> f32 {
+        self.difficulty
+    }
+
+    // The controller setting the spawn rate, on adaptive difficulty
+    pub(crate) fn adaptive(&self) -> Option<&AdaptiveController> {
+        self.adaptive.as_ref()
+    }
+
+    pub(crate) fn stats(&self) -> &TypingStats {
+        &self.stats
+    }
+
+    pub(crate) fn set_matching(&mut self, matching: Matching) {
+        self.matching = matching;
+    }
+
+    pub(crate) fn matching(&self) -> Matching {
+        self.matching
+    }
+
+    pub(crate) fn set_punctuate(&mut self, punctuate: bool) {
+        self.punctuate = punctuate;
+    }
+
+    pub(crate) fn set_focus(&mut self, focus: Focus) {
+        self.focus = focus;
+    }
+
+    pub(crate) fn focus(&self) -> &Focus {
+        &self.focus
+    }
+
+    pub(crate) fn key_stats(&self) -> &KeyStats {
+        &self.key_stats
+    }
+
+    pub(crate) fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    pub(crate) fn leaked(&self) -> usize {
+        self.leaked
+    }
+
+    // Words typed in a row without a wrong key or a leak
+    pub(crate) fn streak(&self) -> u32 {
+        self.streak
+    }
+
+    pub(crate) fn longest_streak(&self) -> u32 {
+        self.longest_streak
+    }
+
+    pub(crate) fn multiplier(&self) -> u32 {
+        (1 + self.streak / STREAK_STEP).min(MAX_MULTIPLIER)
+    }
+
+    // Lives left and the maximum, in modes with lives
+    pub(crate) fn health(&self) -> Option<(u32, u32)> {
+        match self.mode {
+            Mode::Lives { lives, .. } => Some((self.health, lives)),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn is_over(&self) -> bool {
+        self.over
+    }
+
+    // End the game early, like when the player gives up
+    pub(crate) fn end(&mut self) {
+        self.over = true;
+    }
+
+    // Words that reach the edge end the game, cost a life or are simply dropped
+    fn remove_leaked_words(&mut self) {
+        let (leaked, words): (Vec<Word>, Vec<Word>) =
+            self.words.drain(..).partition(|w| w.progress() >= 1.0);
+        self.words = words;
+        if leaked.is_empty() {
+            return;
+        }
+        self.leaked += leaked.len();
+        if let Some(adaptive) = &mut self.adaptive {
+            leaked.iter().for_each(|_| adaptive.record(false));
+        }
+        self.streak = 0;
+        for word in leaked.iter() {
+            self.word_slots[word.y] = false;
+        }
+        match self.mode {
+            Mode::Classic => self.over = true,
+            Mode::Lives { .. } => {
+                self.health = self.health.saturating_sub(leaked.len() as u32);
+                self.over = self.health == 0;
+            }
+            _ => {}
+        }
+    }
+
+    fn spawn_new_word(&mut self) {
+        if self.word_pool.is_empty() {
+            self.shuffle_words();
+        }
+        if self.word_pool.is_empty() {
+            return;
+        }
+
+        let Some(lane) = self.next_lane() else {
+            return;
+        };
+        self.word_slots[lane] = true;
+
+        // Take the next word, skipping words already on the screen unless the pool
+        // has nothing else. Skipped words stay next in line.
+        let index = (0..self.word_pool.len())
+            .rev()
+            .find(|&i| {
+                let form = self.matching.form(&self.word_pool[i]);
+                !self.words.iter().any(|w| w.form == form)
+            })
+            .unwrap_or(self.word_pool.len() - 1);
+        let new_word = self.word_pool.remove(index);
+
+        let jitter = self.curve.speed_jitter;
+        let jitter = if jitter > 0.0 {
+            self.rng.gen_range(-jitter..jitter)
+        } else {
+            0.0
+        };
+        // A sentence takes as long to cross the screen as its words would one
+        // after the other
+        let words = new_word.split_whitespace().count().max(1);
+        let speed = self.curve.speed(self.difficulty, jitter) / words as f32;
+
+        let text = new_word.nfc().collect::<String>();
+        let text = if self.matching.strict {
+            text
+        } else {
+            text.to_lowercase()
+        };
+        let text = if self.punctuate && words == 1 {
+            self.add_punctuation(text)
+        } else {
+            text
+        };
+        let form = self.matching.form(&text);
+        self.words
+            .push(Word::new(new_word, text, form, lane, speed));
+    }
+
+    // Shuffle all words into the pool, favouring words with the focus keys. Each
+    // word gets a random key that tends to be larger the heavier it is, and the
+    // largest keys come first (Efraimidis and Spirakis).
+    fn shuffle_words(&mut self) {
+        let mut keyed: Vec<(f32, String)> = self
+            .all_words
+            .iter()
+            .map(|word| {
+                let weight = if self.focus.is_empty() {
+                    1.0
+                } else {
+                    self.focus.weight(word)
+                };
+                (self.word_rng.gen::<f32>().powf(1.0 / weight), word.clone())
+            })
+            .collect();
+        keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+        self.word_pool = keyed.into_iter().map(|(_, word)| word).collect();
+    }
+
+    // The next free lane from the shuffled lanes. Lanes that are taken are skipped,
+    // and stay next in line.
+    fn next_lane(&mut self) -> Option<usize> {
+        if !self.word_slots.contains(&false) {
+            return None;
+        }
+        if !self.lane_pool.iter().any(|&lane| !self.word_slots[lane]) {
+            self.lane_pool = (0..self.word_slots.len()).collect();
+            self.lane_pool.shuffle(&mut self.lane_rng);
+        }
+        let index = self
+            .lane_pool
+            .iter()
+            .rposition(|&lane| !self.word_slots[lane])?;
+        Some(self.lane_pool.remove(index))
+    }
+
+    // Capitalise some words and end some with punctuation
+    fn add_punctuation(&mut self, word: String) -> String {
+        let mut word = if self.rng.gen_bool(CAPITAL_CHANCE) {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect())
+                .unwrap_or(word)
+        } else {
+            word
+        };
+        if self.rng.gen_bool(PUNCTUATION_CHANCE) {
+            word.push(PUNCTUATION[self.rng.gen_range(0..PUNCTUATION.len())]);
+        }
+        word
+    }
+
+    // Of words with the same text, only the one nearest the edge is captured
+    fn check_if_typed(&mut self, input: &str) -> bool {
+        let Some(index) = self.nearest(|w| w.form == input) else {
+            return false;
+        };
+        let word = self.words.remove(index);
+        self.capture(&word);
+        true
+    }
+
+    // Score a word that was typed and free its lane
+    fn capture(&mut self, word: &Word) {
+        self.stats.record_word();
+        if let Some(adaptive) = &mut self.adaptive {
+            adaptive.record(true);
+        }
+        let score = scoring::score_word(&word.text, word.progress(), word.speed, self.multiplier());
+        self.score.add(&score);
+        self.word_slots[word.y] = false;
+        self.streak += 1;
+        self.longest_streak = self.longest_streak.max(self.streak);
+        if let Mode::Lives { lives, regen } = self.mode {
+            if regen > 0 && self.streak.is_multiple_of(regen) {
+                self.health = (self.health + 1).min(lives);
+            }
+        }
+        if let Mode::WordCount { words } = self.mode {
+            if self.stats.words >= words as usize {
+                self.over = true;
+            }
+        }
+    }
+
+    // Log a keystroke against the character expected by the word nearest the
+    // edge that the input matches. A miss is logged against the word that
+    // matched the input before it. Keys typed after a mistake expect nothing, as
+    // they follow from the mistake.
+    fn record_expected_key(&mut self, input: &str, correct: bool) {
+        let typed: Vec<char> = input.chars().collect();
+        let Some((&key, before)) = typed.split_last() else {
+            return;
+        };
+        let matched = if correct {
+            input.to_string()
+        } else {
+            before.iter().collect()
+        };
+        let expected = self
+            .nearest(|w| w.form.starts_with(&matched))
+            .and_then(|i| self.words[i].form.chars().nth(typed.len() - 1));
+        self.record_key_against(key, expected, typed.len() == 1, correct);
+    }
+
+    // Log a keystroke against the character it was expected to be, and time it
+    // from the previous correct key unless it starts a word
+    fn record_key_against(
+        &mut self,
+        key: char,
+        expected: Option<char>,
+        first: bool,
+        correct: bool,
+    ) {
+        let Some(expected) = expected else {
+            self.last_key = None;
+            return;
+        };
+        self.key_stats.record_key(expected, correct);
+
+        let now = self.stats.elapsed();
+        if let (Some((previous, time)), false, true) = (self.last_key, first, correct) {
+            self.key_stats.record_bigram(previous, key, now - time);
+        }
+        self.last_key = if correct { Some((key, now)) } else { None };
+    }
+
+    // The word nearest the edge that starts with the text
+    pub(crate) fn target(&self, text: &str) -> Option<&Word> {
+        let input = self.matching.form(text);
+        self.nearest(|w| w.form.starts_with(&input))
+            .map(|index| &self.words[index])
+    }
+
+    // Index of the word nearest the edge out of those that match
+    fn nearest(&self, matches: impl Fn(&Word) -> bool) -> Option<usize> {
+        self.words
+            .iter()
+            .enumerate()
+            .filter(|(_, w)| matches(w))
+            .max_by(|(_, a), (_, b)| a.progress().total_cmp(&b.progress()))
+            .map(|(index, _)| index)
+    }
+
+    pub(crate) fn is_prefix_of_word(&self, text: &str) -> bool {
+        self.target(text).is_some()
+    }
+}
+
+fn seeded_stream(seed: u64, stream: u64) -> ChaCha8Rng {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    rng.set_stream(stream);
+    rng
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::difficulty::Difficulty;
+
+    fn engine_with(words: &[&str]) -> Engine {
+        Engine::new(
+            words.iter().map(|w| w.to_string()).collect(),
+            42,
+            40,
+            Mode::Classic,
+            DifficultyCurve::default(),
+        )
+    }
+
+    #[test]
+    fn spawns_first_word_after_delay() {
+        let mut engine = engine_with(&["alpha", "beta"]);
+        engine.tick(Duration::from_millis(100));
+        assert!(engine.words().is_empty());
+
+        engine.tick(FIRST_SPAWN);
+        assert_eq!(engine.words().len(), 1);
+        assert!(engine.words()[0].y < engine.lanes());
+    }
+
+    #[test]
+    fn zero_spawn_rate_still_spawns() {
+        let curve = DifficultyCurve {
+            spawn_rate: 0.0,
+            ramp_per_point: 0.0,
+            ..DifficultyCurve::default()
+        };
+        let mut engine = Engine::new(vec!["alpha".to_string()], 1, 40, Mode::Zen, curve);
+        engine.tick(FIRST_SPAWN);
+        assert_eq!(engine.words().len(), 1);
+        assert!(engine.difficulty() > 0.0);
+    }
+
+    #[test]
+    fn spawn_interval_follows_difficulty() {
+        let mut engine = engine_with(&["a", "b", "c", "d"]);
+        engine.tick(FIRST_SPAWN);
+        assert_eq!(engine.difficulty(), 30.0);
+
+        // At a difficulty of 30 a word spawns every two seconds
+        engine.tick(Duration::from_millis(1900));
+        assert_eq!(engine.words().len(), 1);
+        engine.tick(Duration::from_millis(100));
+        assert_eq!(engine.words().len(), 2);
+    }
+
+    #[test]
+    fn same_seed_spawns_same_words() {
+        let pool: Vec<String> = (0..50).map(|i| format!("word{}", i)).collect();
+        let mut first = Engine::new(
+            pool.clone(),
+            7,
+            40,
+            Mode::Classic,
+            DifficultyCurve::default(),
+        );
+        let mut second = Engine::new(pool, 7, 40, Mode::Classic, DifficultyCurve::default());
+        for _ in 0..10 {
+            first.tick(Duration::from_secs(1));
+            second.tick(Duration::from_secs(1));
+        }
+        let describe = |engine: &Engine| {
+            engine
+                .words()
+                .iter()
+                .map(|w| (w.text.to_owned(), w.y, w.progress()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(describe(&first), describe(&second));
+    }
+
+    #[test]
+    fn typing_does_not_change_the_seeded_words() {
+        let pool: Vec<String> = (0..50).map(|i| format!("word{}", i)).collect();
+        let spawned = |typist: bool| {
+            let mut engine =
+                Engine::new(pool.clone(), 7, 40, Mode::Zen, DifficultyCurve::default());
+            let mut spawned = vec![];
+            for _ in 0..200 {
+                let before = engine.words().len();
+                engine.tick(Duration::from_millis(100));
+                if engine.words().len() > before {
+                    let word = engine.words().last().unwrap();
+                    spawned.push((word.text.to_owned(), word.y));
+                    if typist {
+                        let text = word.text.to_owned();
+                        assert!(engine.on_input(&text));
+                    }
+                }
+            }
+            spawned
+        };
+        // The typist scores points and so gets words sooner and faster, but the
+        // same words in the same lanes
+        let typed = spawned(true);
+        let idle = spawned(false);
+        assert!(typed.len() > idle.len());
+        assert_eq!(typed[..idle.len()], idle[..]);
+    }
+
+    #[test]
+    fn typing_a_word_captures_it_and_scores() {
+        let mut engine = engine_with(&["Alpha"]);
+        engine.tick(FIRST_SPAWN);
+        let lane = engine.words()[0].y;
+
+        assert!(!engine.on_input("alp"));
+        assert!(engine.on_input("ALPHA"));
+        assert!(engine.words().is_empty());
+        assert!(!engine.word_slots[lane]);
+        assert!(engine.score() > 0.0);
+        assert_eq!(engine.stats().words, 1);
+    }
+
+    #[test]
+    fn keeps_duplicate_words_off_the_screen() {
+        let mut engine = engine_with(&["alpha", "alpha", "beta", "beta", "gamma"]);
+        engine.tick(FIRST_SPAWN);
+        for _ in 0..2 {
+            engine.tick(Duration::from_secs(2));
+        }
+        let mut texts: Vec<&str> = engine.words().iter().map(|w| w.text.as_str()).collect();
+        texts.sort();
+        assert_eq!(texts, vec!["alpha", "beta", "gamma"]);
+    }
+
+    #[test]
+    fn skipped_duplicates_keep_their_place() {
+        let pool = vec!["alpha", "alpha", "beta", "gamma", "delta", "alpha"];
+        let spawned = |typist: bool| {
+            let mut engine = engine_with(&pool);
+            let mut spawned = vec![];
+            for _ in 0..6 {
+                engine.tick(Duration::from_secs(2));
+                let text = engine.words().last().unwrap().text.to_owned();
+                if typist {
+                    assert!(engine.on_input(&text));
+                }
+                spawned.push(text);
+            }
+            spawned
+        };
+        let typed = spawned(true);
+        let untyped = spawned(false);
+        // Without typing, a second alpha waits until only alphas are left, and the
+        // other words come in the same order
+        let second_alpha = untyped
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| *t == "alpha")
+            .nth(1)
+            .unwrap()
+            .0;
+        assert!(untyped[second_alpha..].iter().all(|t| t == "alpha"));
+        let others = |texts: &[String]| {
+            texts
+                .iter()
+                .filter(|t| *t != "alpha")
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(others(&typed), others(&untyped));
+        assert_eq!(others(&untyped).len(), 3);
+    }
+
+    #[test]
+    fn captures_only_the_nearest_duplicate() {
+        let mut engine = engine_with(&["alpha"; 3]);
+        engine.tick(FIRST_SPAWN);
+        engine.tick(Duration::from_secs(2));
+        assert_eq!(engine.words().len(), 2);
+        let nearest = engine.words()[0]
+            .progress()
+            .max(engine.words()[1].progress());
+
+        assert!(engine.on_input("alpha"));
+        assert_eq!(engine.words().len(), 1);
+        assert!(engine.words()[0].progress() < nearest);
+        assert_eq!(engine.stats().words, 1);
+    }
+
+    #[test]
+    fn matches_composed_and_jamo_input() {
+        let mut engine = engine_with(&["cafe\u{301}"]);
+        engine.tick(FIRST_SPAWN);
+        assert_eq!(engine.words()[0].text, "café");
+        assert!(engine.on_input("CAFÉ"));
+
+        let mut engine = engine_with(&["한글"]);
+        engine.set_matching(Matching {
+            jamo: true,
+            ..Matching::default()
+        });
+        engine.tick(FIRST_SPAWN);
+        assert!(!engine.on_input("ㅎ"));
+        assert!(!engine.on_input("하"));
+        assert!(!engine.on_input("하ㄴ"));
+        assert!(engine.on_input("한그ㄹ"));
+        assert_eq!(engine.stats().correct, engine.stats().typed);
+    }
+
+    #[test]
+    fn sentences_keep_their_case_and_move_slower() {
+        let mut engine = engine_with(&["Fish.", "Time flies like an arrow."]);
+        engine.set_matching(Matching {
+            strict: true,
+            ..Matching::default()
+        });
+        engine.tick(FIRST_SPAWN);
+        engine.tick(Duration::from_secs(2));
+        let sentence = engine.target("T").unwrap().clone();
+        let word = engine.target("F").unwrap().clone();
+        assert_eq!(sentence.text, "Time flies like an arrow.");
+        assert!(sentence.speed < word.speed);
+
+        assert!(!engine.on_input("time"));
+        assert!(!engine.on_input("Time flies like an arrow"));
+        assert!(engine.on_input("Time flies like an arrow."));
+        assert!(!engine.on_input("fish."));
+        assert!(engine.on_input("Fish."));
+    }
+
+    #[test]
+    fn strict_words_keep_their_case_and_get_punctuation() {
+        let mut engine = engine_with(&["London"; 50]);
+        engine.set_matching(Matching {
+            strict: true,
+            ..Matching::default()
+        });
+        engine.tick(FIRST_SPAWN);
+        assert_eq!(engine.words()[0].text, "London");
+        assert!(!engine.on_input("london"));
+
+        let pool: Vec<String> = (0..100).map(|i| format!("word{}", i)).collect();
+        let mut engine = Engine::new(pool, 5, 40, Mode::Zen, DifficultyCurve::default());
+        engine.set_matching(Matching {
+            strict: true,
+            ..Matching::default()
+        });
+        engine.set_punctuate(true);
+        let mut texts = vec![];
+        for _ in 0..40 {
+            engine.tick(Duration::from_secs(2));
+            texts.push(engine.words().last().unwrap().text.clone());
+            let typed = texts.last().unwrap().clone();
+            assert!(engine.on_input(&typed));
+        }
+        assert!(texts.iter().any(|t| t.starts_with('W')));
+        assert!(texts.iter().any(|t| t.ends_with(PUNCTUATION)));
+        assert!(texts
+            .iter()
+            .any(|t| t.starts_with('w') && t.ends_with(char::is_numeric)));
+    }
+
+    #[test]
+    fn keystrokes_are_checked_against_word_prefixes() {
+        let mut engine = engine_with(&["alpha"]);
+        engine.tick(FIRST_SPAWN);
+
+        engine.on_input("a");
+        engine.on_input("ax");
+        engine.on_backspace();
+        assert_eq!(engine.stats().typed, 2);
+        assert_eq!(engine.stats().correct, 1);
+        assert_eq!(engine.stats().backspaces, 1);
+
+        // The miss counts against the letter that was expected
+        assert_eq!(engine.key_stats().key('a').unwrap().hits, 1);
+        assert_eq!(engine.key_stats().key('l').unwrap().misses, 1);
+        assert!(engine.key_stats().key('x').is_none());
+    }
+
+    #[test]
+    fn correct_keys_count_for_the_word_they_match() {
+        let mut engine = engine_with(&["alpha", "atlas"]);
+        engine.tick(FIRST_SPAWN);
+        engine.tick(Duration::from_secs(2));
+        // Atlas is nearer, but "al" only matches alpha
+        assert_eq!(engine.target("a").unwrap().text, "atlas");
+        engine.on_input("a");
+        engine.on_input("al");
+        assert_eq!(engine.key_stats().key('l').unwrap().hits, 1);
+        assert!(engine.key_stats().key('t').is_none());
+
+        // A miss still counts against the word the input matched before it
+        engine.on_input("alx");
+        assert_eq!(engine.key_stats().key('p').unwrap().misses, 1);
+    }
+
+    #[test]
+    fn times_bigrams_between_correct_keys() {
+        let pool = vec!["alpha".to_string(); 10];
+        let mut engine = Engine::new(pool, 3, 40, Mode::Zen, DifficultyCurve::default());
+        for _ in 0..3 {
+            engine.tick(Duration::from_secs(2));
+            for i in 1..=5 {
+                engine.tick(Duration::from_millis(100));
+                engine.on_input(&"alpha"[..i]);
+            }
+        }
+        // A mistake breaks the timing, so the slow 'p' after it is not counted
+        engine.tick(Duration::from_secs(2));
+        engine.on_input("a");
+        engine.on_input("ax");
+        engine.on_backspace();
+        engine.tick(Duration::from_secs(1));
+        engine.on_input("al");
+
+        let slowest = engine.key_stats().slowest_bigrams(5);
+        assert_eq!(slowest.len(), 4);
+        assert!(slowest
+            .iter()
+            .all(|(_, time)| *time == Duration::from_millis(100)));
+    }
+
+    #[test]
+    fn lock_on_shoots_letters_off_the_nearest_word() {
+        let mut engine = engine_with(&["alpha", "atlas"]);
+        engine.tick(FIRST_SPAWN);
+        engine.tick(Duration::from_secs(2));
+        let nearest = engine.target("a").unwrap().text.clone();
+
+        assert!(!engine.on_lock_key('A'));
+        assert_eq!(engine.locked().unwrap().text, nearest);
+        for key in nearest.chars().skip(1).take(3) {
+            assert!(!engine.on_lock_key(key));
+        }
+        // A wrong key is a miss and keeps the lock
+        assert!(!engine.on_lock_key(if nearest == "alpha" { 't' } else { 'l' }));
+        assert_eq!(engine.locked().unwrap().shot, 4);
+        assert!(engine.on_lock_key(nearest.chars().last().unwrap()));
+        assert!(engine.locked().is_none());
+        assert_eq!(engine.words().len(), 1);
+        assert_eq!(engine.stats().words, 1);
+        assert_eq!((engine.stats().typed, engine.stats().correct), (6, 5));
+    }
+
+    #[test]
+    fn releasing_the_lock_restores_the_word() {
+        let mut engine = engine_with(&["alpha"]);
+        engine.tick(FIRST_SPAWN);
+        assert!(!engine.on_lock_key('x'));
+        assert!(engine.locked().is_none());
+        assert!(engine.key_stats().is_empty());
+
+        engine.on_lock_key('a');
+        engine.on_lock_key('l');
+        engine.release_lock();
+        assert!(engine.locked().is_none());
+        assert_eq!(engine.words()[0].shot, 0);
+        assert_eq!(engine.key_stats().key('a').unwrap().hits, 1);
+    }
+
+    #[test]
+    fn word_reaching_the_edge_ends_the_game() {
+        let mut engine = engine_with(&["alpha", "beta"]);
+        engine.tick(FIRST_SPAWN);
+        assert!(!engine.is_over());
+
+        engine.tick(Duration::from_secs(200));
+        assert!(engine.is_over());
+    }
+
+    #[test]
+    fn shrinking_moves_words_into_free_lanes() {
+        let pool: Vec<String> = (0..20).map(|i| format!("word{}", i)).collect();
+        let mut engine = Engine::new(pool, 3, 40, Mode::Zen, DifficultyCurve::default());
+        for _ in 0..12 {
+            engine.tick(Duration::from_secs(2));
+        }
+        assert_eq!(engine.words().len(), 12);
+
+        engine.resize(MIN_LANES);
+        assert_eq!(engine.lanes(), MIN_LANES);
+        assert_eq!(engine.words().len(), MIN_LANES);
+        assert!(engine.words().iter().all(|w| w.y < MIN_LANES));
+        assert!(engine.word_slots.iter().all(|&taken| taken));
+        assert_eq!(engine.word_pool.len(), 20 - MIN_LANES);
+    }
+
+    #[test]
+    fn shrinking_returns_words_without_punctuation() {
+        let pool: Vec<String> = (0..20).map(|i| format!("word{}", i)).collect();
+        let mut engine = Engine::new(pool, 3, 40, Mode::Zen, DifficultyCurve::default());
+        engine.set_punctuate(true);
+        for _ in 0..12 {
+            engine.tick(Duration::from_secs(2));
+        }
+        engine.resize(MIN_LANES);
+        assert!(engine
+            .word_pool
+            .iter()
+            .all(|w| w.starts_with("word") && w.ends_with(char::is_numeric)));
+    }
+
+    fn engine_in(mode: Mode) -> Engine {
+        let pool: Vec<String> = (0..20).map(|i| format!("word{}", i)).collect();
+        Engine::new(pool, 5, 40, mode, DifficultyCurve::default())
+    }
+
+    #[test]
+    fn lives_mode_ends_after_enough_leaks() {
+        let mut engine = engine_in(Mode::Lives { lives: 2, regen: 0 });
+        engine.tick(FIRST_SPAWN);
+        engine.tick(Duration::from_secs(200));
+        assert_eq!(engine.leaked(), 1);
+        assert_eq!(engine.health(), Some((1, 2)));
+        assert!(!engine.is_over());
+        assert_eq!(engine.words().len(), 1);
+        assert_eq!(engine.words()[0].progress(), 0.0);
+
+        engine.tick(Duration::from_secs(200));
+        assert_eq!(engine.leaked(), 2);
+        assert!(engine.is_over());
+    }
+
+    #[test]
+    fn zen_mode_never_ends() {
+        let mut engine = engine_in(Mode::Zen);
+        for _ in 0..100 {
+            engine.tick(Duration::from_secs(30));
+        }
+        assert!(engine.leaked() > 20);
+        assert!(!engine.is_over());
+    }
+
+    #[test]
+    fn time_attack_ends_when_time_runs_out() {
+        let mut engine = engine_in(Mode::TimeAttack { seconds: 60 });
+        for _ in 0..59 {
+            engine.tick(Duration::from_secs(1));
+        }
+        assert!(!engine.is_over());
+        engine.tick(Duration::from_secs(1));
+        assert!(engine.is_over());
+    }
+
+    #[test]
+    fn word_count_ends_after_enough_words() {
+        let mut engine = Engine::new(
+            vec!["alpha".to_string(); 5],
+            5,
+            40,
+            Mode::WordCount { words: 2 },
+            DifficultyCurve::default(),
+        );
+        engine.tick(FIRST_SPAWN);
+        assert!(engine.on_input("alpha"));
+        assert!(!engine.is_over());
+        engine.tick(Duration::from_secs(3));
+        assert!(engine.on_input("alpha"));
+        assert!(engine.is_over());
+    }
+
+    #[test]
+    fn typing_streaks_restore_lives() {
+        let pool = vec!["alpha".to_string(); 10];
+        let mut engine = Engine::new(
+            pool,
+            5,
+            40,
+            Mode::Lives { lives: 3, regen: 2 },
+            DifficultyCurve::default(),
+        );
+        engine.tick(FIRST_SPAWN);
+        engine.tick(Duration::from_secs(200));
+        assert_eq!(engine.health(), Some((2, 3)));
+
+        for _ in 0..2 {
+            engine.tick(Duration::from_secs(3));
+            assert!(engine.on_input("alpha"));
+        }
+        assert_eq!(engine.health(), Some((3, 3)));
+
+        // Health never goes above the maximum
+        for _ in 0..2 {
+            engine.tick(Duration::from_secs(3));
+            assert!(engine.on_input("alpha"));
+        }
+        assert_eq!(engine.health(), Some((3, 3)));
+    }
+
+    #[test]
+    fn streaks_raise_the_multiplier_until_a_mistake() {
+        let mut engine = Engine::new(
+            vec!["alpha".to_string(); 40],
+            5,
+            40,
+            Mode::Zen,
+            DifficultyCurve::default(),
+        );
+        for _ in 0..12 {
+            engine.tick(Duration::from_secs(2));
+            assert!(engine.on_input("alpha"));
+        }
+        assert_eq!(engine.streak(), 12);
+        assert_eq!(engine.multiplier(), 3);
+
+        engine.tick(Duration::from_secs(2));
+        assert!(!engine.on_input("x"));
+        assert_eq!(engine.streak(), 0);
+        assert_eq!(engine.multiplier(), 1);
+        assert_eq!(engine.longest_streak(), 12);
+    }
+
+    #[test]
+    fn leaks_reset_the_streak() {
+        let mut engine = Engine::new(
+            vec!["alpha".to_string(); 40],
+            5,
+            40,
+            Mode::Zen,
+            DifficultyCurve::default(),
+        );
+        engine.tick(FIRST_SPAWN);
+        assert!(engine.on_input("alpha"));
+        engine.tick(Duration::from_secs(2));
+        engine.tick(Duration::from_secs(200));
+        assert_eq!(engine.leaked(), 1);
+        assert_eq!(engine.streak(), 0);
+        assert_eq!(engine.longest_streak(), 1);
+    }
+
+    #[test]
+    fn multiplier_is_capped() {
+        let mut engine = Engine::new(
+            vec!["alpha".to_string(); 80],
+            5,
+            40,
+            Mode::Zen,
+            DifficultyCurve::default(),
+        );
+        for _ in 0..40 {
+            engine.tick(Duration::from_secs(2));
+            assert!(engine.on_input("alpha"));
+        }
+        assert_eq!(engine.multiplier(), MAX_MULTIPLIER);
+    }
+
+    #[test]
+    fn curve_limits_words_on_screen() {
+        let pool: Vec<String> = (0..50).map(|i| format!("word{}", i)).collect();
+        let curve = DifficultyCurve {
+            max_words: 3,
+            ..DifficultyCurve::default()
+        };
+        let mut engine = Engine::new(pool, 3, 40, Mode::Zen, curve);
+        for _ in 0..10 {
+            engine.tick(Duration::from_secs(2));
+        }
+        assert_eq!(engine.words().len(), 3);
+    }
+
+    #[test]
+    fn curve_ramps_up_over_time() {
+        let curve = DifficultyCurve {
+            ramp_per_minute: 10.0,
+            speed_jitter: 0.0,
+            ..DifficultyCurve::default()
+        };
+        let mut engine = engine_with(&["alpha", "beta"]);
+        engine.curve = curve;
+        engine.tick(FIRST_SPAWN);
+        let first_speed = engine.words()[0].speed;
+        for _ in 0..60 {
+            engine.tick(Duration::from_secs(1));
+        }
+        assert!(engine.difficulty() > 39.0);
+        assert!(engine.words().iter().any(|w| w.speed > first_speed));
+    }
+
+    #[test]
+    fn adaptive_difficulty_follows_the_player() {
+        let curve = Difficulty::Adaptive.curve(None);
+        let mut engine = Engine::new(vec!["alpha".to_string(); 200], 5, 40, Mode::Zen, curve);
+        for _ in 0..60 {
+            engine.tick(Duration::from_secs(1));
+            for i in 1..=5 {
+                engine.on_input(&"alpha"[..i]);
+            }
+        }
+        assert!(engine.adaptive().unwrap().success_rate() == Some(1.0));
+        assert!(engine.difficulty() > curve.spawn_rate);
+    }
+
+    #[test]
+    fn focus_favours_words_with_weak_keys() {
+        let pool: Vec<String> = (0..100)
+            .map(|i| if i < 20 { "quiz" } else { "tent" }.to_string())
+            .collect();
+        let curve = DifficultyCurve {
+            max_words: 40,
+            ..DifficultyCurve::default()
+        };
+        let mut history = KeyStats::default();
+        history.record_key('q', false);
+        // Counted over a few seeds, as a single game can be unlucky
+        let count_focused = |focus: Focus| {
+            let mut count = 0;
+            for seed in 0..10 {
+                let mut engine = Engine::new(pool.clone(), seed, 40, Mode::Practice, curve);
+                engine.set_focus(focus.clone());
+                for _ in 0..20 {
+                    engine.tick(Duration::from_secs(2));
+                    if engine.words().last().unwrap().text == "quiz" {
+                        count += 1;
+                    }
+                }
+            }
+            count
+        };
+        let focused = count_focused(Focus::from_history(&history));
+        let unfocused = count_focused(Focus::default());
+        assert!(focused > unfocused + 30, "{} vs {}", focused, unfocused);
+    }
+}
@@ -1,30 +1,64 @@
-use std::{
-    error::Error,
-    io::Stdout,
-    thread,
-    time::{Duration, Instant},
-};
+use std::{error::Error, time::Duration};
 
-use crossterm::event::{self, Event, KeyCode};
+use crossterm::event::{Event, KeyCode};
 use tui::{
-    backend::CrosstermBackend,
-    layout::{Alignment, Constraint, Direction, Layout, Rect},
-    text::Spans,
-    widgets::{Block, BorderType, Borders, List, ListItem, Paragraph},
+    backend::Backend,
+    layout::{Alignment, Constraint, Direction, Layout},
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, BorderType, Borders, List, ListItem, Paragraph, Wrap},
     Terminal,
 };
 
-use crate::{GameError, GameState, StatefulList, FRAME_TIME};
+use crate::{
+    game::{events::EventSource, high_score_table, key_heatmap, Screen},
+    high_score::{HighScore, HighScores, TABLE_SIZE},
+    GameState, StatefulList, FRAME_TIME,
+};
 
-pub(crate) fn show_view(
-    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+pub(crate) fn show_view<B: Backend>(
+    terminal: &mut Terminal<B>,
+    events: &mut impl EventSource,
     game_state: &mut GameState,
-) -> Result<bool, Box<dyn Error>> {
-    let mut last_frame_time = Instant::now();
+    high_scores: &mut HighScores,
+) -> Result<Screen, Box<dyn Error>> {
+    // Record the score in the high-score table
+    let entry = HighScore::from_game(game_state);
+    let rank = high_scores.add(entry.clone());
+    let key_history = &mut game_state.key_history;
+    key_history.merge(game_state.engine.key_stats());
+    // Both are saved even if one fails, and the first error is shown
+    let saved = [high_scores.save(), key_history.save()];
+    let error_message = saved
+        .into_iter()
+        .find_map(|result| result.err())
+        .map(|err| err.to_string())
+        .or_else(|| high_scores.file.error.clone())
+        .or_else(|| key_history.file.error.clone());
+    let category = high_scores.category(
+        &entry.language,
+        entry.mode,
+        entry.difficulty,
+        entry.input_mode,
+        entry.matching,
+        entry.punctuate,
+    );
+    let rank_message = if rank < TABLE_SIZE {
+        format!("New high score! You placed #{}.", rank + 1)
+    } else {
+        format!("You placed #{}.", rank + 1)
+    };
+
+    let stats = game_state.engine.stats();
+    let breakdown = game_state.engine.score_breakdown();
 
     // Create a list of options
-    let mut items =
-        StatefulList::with_items(vec![ListItem::new("Play again?"), ListItem::new("Exit")]);
+    let mut items = StatefulList::with_items(vec![
+        ListItem::new("Play again?"),
+        ListItem::new("Main menu"),
+        ListItem::new("Key stats"),
+        ListItem::new("Exit"),
+    ]);
     items.next();
 
     // Create the list widget and set its items
@@ -39,28 +73,17 @@ pub(crate) fn show_view(
     // Set up the terminal and run the event loop
     terminal.clear()?;
     loop {
-        let elapsed_time = last_frame_time.elapsed();
-        last_frame_time = Instant::now();
+        events.next_frame();
 
         // Get the size of the terminal
         let size = terminal.size()?;
-        if size.height < 47 {
-            return Err(Box::new(GameError(
-                "Console should be at least 47 lines tall",
-            )));
-        }
 
         // Create a layouts and widgets
         let main_pane = Layout::default()
             .direction(Direction::Vertical)
             .margin(1)
             .constraints([Constraint::Min(0)].as_ref())
-            .split(Rect {
-                x: 0,
-                y: 0,
-                width: size.width,
-                height: 47,
-            });
+            .split(size);
         let block = Block::default()
             .borders(Borders::ALL)
             .title(" Type Defender ")
@@ -69,48 +92,105 @@ pub(crate) fn show_view(
         let inner_pane = Layout::default()
             .direction(Direction::Vertical)
             .margin(3)
-            .constraints([Constraint::Length(4), Constraint::Percentage(100)].as_ref())
+            .constraints(
+                [
+                    Constraint::Min(TABLE_SIZE as u16 + 3),
+                    Constraint::Length(7),
+                    Constraint::Length(5),
+                ]
+                .as_ref(),
+            )
             .split(main_pane[0]);
+        let top_pane = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+            .split(inner_pane[0]);
         let end_message_text = vec![
             Spans::from("Game Over!"),
             Spans::from(""),
-            Spans::from(format!("Score: {:.1}", game_state.score)),
+            Spans::from(format!(
+                "Mode: {}, difficulty: {}, input: {}, seed: {}",
+                game_state.mode, game_state.difficulty, game_state.input_mode, game_state.seed
+            )),
+            Spans::from(format!("Score: {:.1}", game_state.engine.score())),
+            Spans::from(format!(
+                "  {:.1} for speed and distance, {:+.1} for word length, {:+.1} for hard \
+                    letters, {:+.1} for streaks",
+                breakdown.speed, breakdown.length, breakdown.difficulty, breakdown.streak
+            )),
+            Spans::from(""),
+            Spans::from(format!(
+                "Time: {}s, words typed: {}, leaked: {}",
+                stats.elapsed().as_secs(),
+                stats.words,
+                game_state.engine.leaked()
+            )),
+            Spans::from(format!(
+                "Longest streak: {} words",
+                game_state.engine.longest_streak()
+            )),
+            Spans::from(format!(
+                "Characters: {} typed, {} correct, {} backspaces",
+                stats.typed, stats.correct, stats.backspaces
+            )),
+            Spans::from(format!("Accuracy: {:.1}%", stats.accuracy())),
+            Spans::from(format!(
+                "Speed: {:.1} WPM net, {:.1} WPM gross",
+                stats.net_wpm(),
+                stats.gross_wpm()
+            )),
+            Spans::from(""),
+            Spans::from(rank_message.to_owned()),
+            Spans::from(Span::styled(
+                error_message.clone().unwrap_or_default(),
+                Style::default().fg(Color::Red),
+            )),
         ];
-        let end_message_paragraph = Paragraph::new(end_message_text);
+        let end_message_paragraph = Paragraph::new(end_message_text).wrap(Wrap { trim: true });
+        let mut heatmap_text = vec![Spans::from("Keys this game:")];
+        heatmap_text.extend(key_heatmap::build(game_state.engine.key_stats()));
+        let heatmap_paragraph = Paragraph::new(heatmap_text);
+        let table = high_score_table::build(
+            high_score_table::title(
+                &entry.language,
+                entry.mode,
+                entry.difficulty,
+                entry.input_mode,
+                entry.matching,
+                entry.punctuate,
+            ),
+            &category,
+            Some(rank),
+        );
 
         // Render terminal
         terminal.draw(|f| {
             f.render_widget(block, main_pane[0]);
-            f.render_widget(end_message_paragraph, inner_pane[0]);
-            f.render_widget(list.clone(), inner_pane[1]);
-            f.render_stateful_widget(list.clone(), inner_pane[1], &mut items.state)
+            f.render_widget(end_message_paragraph, top_pane[0]);
+            f.render_widget(table, top_pane[1]);
+            f.render_widget(heatmap_paragraph, inner_pane[1]);
+            f.render_stateful_widget(list.clone(), inner_pane[2], &mut items.state)
         })?;
 
         // Listen for a key press events
         let poll_time = FRAME_TIME
             .checked_sub(Duration::from_millis(5))
             .unwrap_or_else(|| Duration::from_micros(0));
-        if event::poll(poll_time)? {
-            if let Event::Key(key) = event::read()? {
-                match key.code {
-                    KeyCode::Esc => return Ok(false),
-                    KeyCode::Down => items.next(),
-                    KeyCode::Up => items.previous(),
-                    KeyCode::Enter => {
-                        if items.state.selected().unwrap() == 0 {
-                            return Ok(true);
-                        }
-                        return Ok(false);
-                    }
-                    _ => {}
+        if let Some(Event::Key(key)) = events.poll(poll_time)? {
+            match key.code {
+                KeyCode::Esc => return Ok(Screen::Quit),
+                KeyCode::Down => items.next(),
+                KeyCode::Up => items.previous(),
+                KeyCode::Enter => {
+                    return Ok(match items.state.selected().unwrap() {
+                        0 => Screen::Game,
+                        1 => Screen::Home,
+                        2 => Screen::Stats,
+                        _ => Screen::Quit,
+                    })
                 }
+                _ => {}
             }
         }
-
-        // Sleep to maintain desired FPS
-        let time_to_sleep = FRAME_TIME
-            .checked_sub(elapsed_time)
-            .unwrap_or_else(|| Duration::from_micros(0));
-        thread::sleep(time_to_sleep);
     }
 }
@@ -0,0 +1,49 @@
+use std::{
+    io, thread,
+    time::{Duration, Instant},
+};
+
+use crossterm::event::{self, Event};
+
+use crate::FRAME_TIME;
+
+pub(crate) trait EventSource {
+    // Wait up to `timeout` for the next input event
+    fn poll(&mut self, timeout: Duration) -> io::Result<Option<Event>>;
+
+    // Wait for the next frame and return the time since the previous one
+    fn next_frame(&mut self) -> Duration;
+}
+
+pub(crate) struct CrosstermEvents {
+    last_frame_time: Instant,
+}
+
+impl CrosstermEvents {
+    pub(crate) fn new() -> Self {
+        CrosstermEvents {
+            last_frame_time: Instant::now(),
+        }
+    }
+}
+
+impl EventSource for CrosstermEvents {
+    fn poll(&mut self, timeout: Duration) -> io::Result<Option<Event>> {
+        if event::poll(timeout)? {
+            return Ok(Some(event::read()?));
+        }
+        Ok(None)
+    }
+
+    fn next_frame(&mut self) -> Duration {
+        // Sleep to maintain desired FPS
+        let time_to_sleep = FRAME_TIME
+            .checked_sub(self.last_frame_time.elapsed())
+            .unwrap_or_else(|| Duration::from_micros(0));
+        thread::sleep(time_to_sleep);
+
+        let elapsed_time = self.last_frame_time.elapsed();
+        self.last_frame_time = Instant::now();
+        elapsed_time
+    }
+}
@@ -1,245 +1,401 @@
-use std::{
-    error::Error,
-    io::Stdout,
-    thread,
-    time::{Duration, Instant},
-};
+use std::{error::Error, time::Duration};
 
-use crossterm::event::{self, KeyCode};
-use rand::{thread_rng, Rng};
+use crossterm::event::{Event, KeyCode};
 use tui::{
-    backend::CrosstermBackend,
-    layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Style},
+    backend::Backend,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
     text::{Span, Spans},
-    widgets::{Block, Borders, Paragraph, Wrap},
+    widgets::{Block, Borders, Gauge, Paragraph, Wrap},
     Terminal,
 };
 use tui_input::{backend::crossterm::EventHandler, Input};
+use unicode_width::UnicodeWidthStr;
 
-use crate::{Asset, GameError, GameState, Word, FPS, FRAME_TIME};
+use crate::{
+    engine::{Engine, MIN_LANES},
+    game::{
+        events::EventSource,
+        pause_menu::{is_command_key, is_pause_key, PauseAction, PauseMenu},
+        Screen,
+    },
+    language_pack::TextDirection,
+    mode::{InputMode, Mode},
+    word_list::MAX_SENTENCE_WIDTH,
+    GameState, FRAME_TIME,
+};
 
-pub(crate) fn show_view(
-    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+pub(crate) fn show_view<B: Backend>(
+    terminal: &mut Terminal<B>,
+    events: &mut impl EventSource,
     game_state: &mut GameState,
-) -> Result<bool, Box<dyn Error>> {
-    let mut last_frame_time = Instant::now();
-    let mut counter = 20;
-
-    load_words(game_state)?;
+) -> Result<Screen, Box<dyn Error>> {
+    game_state.new_game(lanes_for(terminal.size()?.height));
+    let engine = &mut game_state.engine;
+    let input_mode = game_state.input_mode;
+    let direction = game_state.word_list.direction();
 
     let mut text_input = Input::default();
+    let mut pause_menu: Option<PauseMenu> = None;
 
     loop {
-        let elapsed_time = last_frame_time.elapsed();
-        last_frame_time = Instant::now();
-        counter -= 1;
+        let elapsed_time = events.next_frame();
+        let poll_time = FRAME_TIME
+            .checked_sub(Duration::from_millis(5))
+            .unwrap_or_else(|| Duration::from_micros(0));
 
         // Calculate the layout for the terminal
         let size = terminal.size()?;
-        if size.height < 47 {
-            return Err(Box::new(GameError(
-                "Console should be at least 47 lines tall",
-            )));
+        let lanes = lanes_for(size.height);
+        if lanes < MIN_LANES || (size.width as usize) < MIN_WIDTH {
+            // Pause the game until the terminal is large enough again
+            let message = Paragraph::new(format!(
+                "The terminal is too small to play. Please make it at least {} columns wide \
+                 and {} lines tall.",
+                MIN_WIDTH,
+                MIN_LANES + LAYOUT_HEIGHT
+            ))
+            .wrap(Wrap { trim: true });
+            terminal.draw(|f| f.render_widget(message, size))?;
+            if let Some(Event::Key(key)) = events.poll(poll_time)? {
+                if key.code == KeyCode::Esc {
+                    return Ok(Screen::Home);
+                }
+            }
+            continue;
         }
+        engine.resize(lanes);
 
         let main_pane = Layout::default()
             .direction(Direction::Vertical)
             .margin(1)
-            .constraints([Constraint::Length(42), Constraint::Length(1)].as_ref())
-            .split(Rect {
-                x: 0,
-                y: 0,
-                width: size.width,
-                height: 47,
-            });
+            .constraints([Constraint::Min(0), Constraint::Length(3)].as_ref())
+            .split(size);
         let bottom_pane = Layout::default()
             .direction(Direction::Horizontal)
             .margin(0)
             .constraints(
                 [
-                    Constraint::Percentage(50),
-                    Constraint::Percentage(25),
-                    Constraint::Percentage(25),
+                    Constraint::Percentage(30),
+                    Constraint::Percentage(14),
+                    Constraint::Percentage(14),
+                    Constraint::Percentage(14),
+                    Constraint::Percentage(14),
+                    Constraint::Percentage(14),
                 ]
                 .as_ref(),
             )
             .split(main_pane[1]);
 
-        if counter == 0 {
-            spawn_new_word(game_state);
-            game_state.wpm = 30.0 + game_state.score / 10.0;
-            counter = ((60.0 / game_state.wpm) * FPS as f32) as usize
-        }
-
-        // Draw the words
-        if !generate_display(game_state, size)? {
-            return Ok(true);
-        }
-
-        if !text_input.value().is_empty()
-            && check_if_typed(game_state, text_input.value().to_owned())
-        {
-            text_input.reset();
+        if pause_menu.is_none() {
+            engine.tick(elapsed_time);
+            if engine.is_over() {
+                return Ok(Screen::End);
+            }
         }
 
-        let poll_time = FRAME_TIME
-            .checked_sub(Duration::from_millis(5))
-            .unwrap_or_else(|| Duration::from_micros(0));
-        if event::poll(poll_time)? {
-            if let crossterm::event::Event::Key(key) = event::read()? {
-                match key.code {
-                    KeyCode::Enter => {
-                        text_input.reset();
-                        continue;
+        match events.poll(poll_time)? {
+            Some(Event::FocusLost) if game_state.pause_on_focus_loss => {
+                pause_menu.get_or_insert_with(PauseMenu::new);
+            }
+            Some(Event::Key(key)) if pause_menu.is_some() => {
+                match pause_menu.as_mut().unwrap().handle_key(key) {
+                    Some(PauseAction::Resume) => pause_menu = None,
+                    Some(PauseAction::Restart) => return Ok(Screen::Game),
+                    Some(PauseAction::EndGame) => {
+                        engine.end();
+                        return Ok(Screen::End);
                     }
-                    KeyCode::Char(' ') => continue,
-                    KeyCode::Esc => return Ok(false),
-                    _ => text_input.handle_event(&crossterm::event::Event::Key(key)),
-                };
+                    Some(PauseAction::QuitToMenu) => return Ok(Screen::Home),
+                    Some(PauseAction::TogglePauseOnFocusLoss) => {
+                        game_state.pause_on_focus_loss = !game_state.pause_on_focus_loss
+                    }
+                    None => {}
+                }
             }
+            // Keys shoot letters off the locked word instead of going into the input
+            Some(Event::Key(key)) if input_mode == InputMode::LockOn => match key.code {
+                KeyCode::Esc if engine.locked().is_some() => engine.release_lock(),
+                _ if is_pause_key(&key) => pause_menu = Some(PauseMenu::new()),
+                _ if is_command_key(&key) => {}
+                KeyCode::Backspace => {
+                    engine.on_backspace();
+                    engine.release_lock();
+                }
+                // Spaces only count between the words of a sentence
+                KeyCode::Char(' ')
+                    if engine
+                        .locked()
+                        .is_some_and(|w| w.form.chars().nth(w.shot) == Some(' ')) =>
+                {
+                    engine.on_lock_key(' ');
+                }
+                KeyCode::Enter | KeyCode::Char(' ') => continue,
+                KeyCode::Char(c) => {
+                    engine.on_lock_key(c);
+                }
+                _ => {}
+            },
+            Some(Event::Key(key)) => match key.code {
+                _ if is_pause_key(&key) => pause_menu = Some(PauseMenu::new()),
+                // An IME may send Enter or Space to finish a syllable, so input
+                // that still matches a word is kept
+                KeyCode::Enter => {
+                    if !engine.is_prefix_of_word(text_input.value()) {
+                        text_input.reset();
+                    }
+                    continue;
+                }
+                // Spaces only go into the input between the words of a sentence
+                KeyCode::Char(' ')
+                    if !engine.is_prefix_of_word(&format!("{} ", text_input.value())) =>
+                {
+                    continue
+                }
+                KeyCode::Backspace => {
+                    engine.on_backspace();
+                    text_input.handle_event(&Event::Key(key));
+                }
+                KeyCode::Char(_) if !is_command_key(&key) => {
+                    text_input.handle_event(&Event::Key(key));
+                    if engine.on_input(text_input.value()) {
+                        text_input.reset();
+                    }
+                }
+                _ => {
+                    text_input.handle_event(&Event::Key(key));
+                }
+            },
+            _ => {}
+        }
+
+        // The input shows what has been shot off the locked word, which goes when it leaks
+        if input_mode == InputMode::LockOn {
+            text_input = Input::new(
+                engine
+                    .locked()
+                    .map(|w| w.form.chars().take(w.shot).collect())
+                    .unwrap_or_default(),
+            );
         }
 
         // Draw the text
         terminal.draw(|f| {
+            let title = match engine.mode() {
+                Mode::Practice => format!("Type Defender - Focusing on: {}", engine.focus()),
+                _ => "Type Defender".to_string(),
+            };
             let block = Block::default()
-                .title("Type Defender")
+                .title(title)
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(Color::White));
 
-            let paragraph = Paragraph::new(game_state.display_rows.to_owned())
-                .block(block)
-                .style(Style::default().fg(Color::White))
-                .alignment(tui::layout::Alignment::Left)
-                .wrap(Wrap { trim: false });
+            let paragraph = Paragraph::new(generate_display(
+                engine,
+                main_pane[0].width.saturating_sub(2),
+                text_input.value(),
+                input_mode,
+                direction,
+            ))
+            .block(block)
+            .style(Style::default().fg(Color::White))
+            .alignment(tui::layout::Alignment::Left);
             f.render_widget(paragraph, main_pane[0]);
 
             let scroll = text_input.visual_scroll((bottom_pane[0].width.max(3) - 3) as usize);
+            let input_style =
+                if text_input.value().is_empty() || engine.is_prefix_of_word(text_input.value()) {
+                    Style::default()
+                } else {
+                    Style::default().fg(Color::Red)
+                };
             let text_input_paragraph = Paragraph::new(text_input.value())
-                .style(Style::default())
+                .style(input_style)
                 .scroll((0, scroll as u16))
-                .block(Block::default().borders(Borders::ALL).title("Input"));
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_style(input_style)
+                        .title("Input"),
+                );
             f.render_widget(text_input_paragraph, bottom_pane[0]);
             f.set_cursor(
                 bottom_pane[0].x + ((text_input.visual_cursor()).max(scroll) - scroll) as u16 + 1,
                 bottom_pane[0].y + 1,
             );
 
-            let score_label = Paragraph::new(format!("{:.1}", game_state.score))
-                .block(Block::default().borders(Borders::ALL).title("Score"));
+            let score_label =
+                Paragraph::new(format!("{:.1} x{}", engine.score(), engine.multiplier())).block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title(format!("Score, streak {}", engine.streak())),
+                );
             f.render_widget(score_label, bottom_pane[1]);
 
-            let wpm_label = Paragraph::new(format!("{:.1}", game_state.wpm))
-                .block(Block::default().borders(Borders::ALL).title("WPM"));
-            f.render_widget(wpm_label, bottom_pane[2])
+            let wpm_label = Paragraph::new(format!(
+                "{:.0} ({:.0} gross)",
+                engine.stats().rolling_net_wpm(),
+                engine.stats().rolling_gross_wpm()
+            ))
+            .block(Block::default().borders(Borders::ALL).title("WPM"));
+            f.render_widget(wpm_label, bottom_pane[2]);
+
+            let accuracy_label = Paragraph::new(format!("{:.1}%", engine.stats().accuracy()))
+                .block(Block::default().borders(Borders::ALL).title("Accuracy"));
+            f.render_widget(accuracy_label, bottom_pane[3]);
+
+            let difficulty_label = match engine.adaptive() {
+                Some(adaptive) => Paragraph::new(format!(
+                    "{:.0}/min ({:.0}%)",
+                    adaptive.rate(),
+                    adaptive.success_rate().unwrap_or(adaptive.target()) * 100.0
+                ))
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title(format!("Target {:.0}%", adaptive.target() * 100.0)),
+                ),
+                None => Paragraph::new(format!(
+                    "{} ({:.0})",
+                    game_state.difficulty,
+                    engine.difficulty()
+                ))
+                .block(Block::default().borders(Borders::ALL).title("Difficulty")),
+            };
+            f.render_widget(difficulty_label, bottom_pane[4]);
+
+            if let Some((health, max_health)) = engine.health() {
+                let health_bar = Gauge::default()
+                    .block(Block::default().borders(Borders::ALL).title("Health"))
+                    .gauge_style(Style::default().fg(health_color(health, max_health)))
+                    .ratio(health as f64 / max_health as f64)
+                    .label(format!("{}/{}", health, max_health));
+                f.render_widget(health_bar, bottom_pane[5]);
+            } else {
+                let mode_label = Paragraph::new(mode_status(engine))
+                    .block(Block::default().borders(Borders::ALL).title("Mode"));
+                f.render_widget(mode_label, bottom_pane[5]);
+            }
+
+            if let Some(pause_menu) = pause_menu.as_mut() {
+                pause_menu.render(f, main_pane[0], game_state.pause_on_focus_loss);
+            }
         })?;
+    }
+}
 
-        // Sleep to maintain desired FPS
-        let time_to_sleep = FRAME_TIME
-            .checked_sub(elapsed_time)
-            .unwrap_or_else(|| Duration::from_micros(0));
-        thread::sleep(time_to_sleep);
+// The column a word starts at. Words move by display width, not bytes, so that
+// a word's last column reaches the border just as it leaks. Words of right to
+// left languages move from the right to the left border.
+fn word_column(progress: f32, width: u16, text: &str, direction: TextDirection) -> usize {
+    let room = (width as usize).saturating_sub(text.width());
+    let moved = (progress.clamp(0.0, 1.0) * room as f32) as usize;
+    match direction {
+        TextDirection::Ltr => moved,
+        TextDirection::Rtl => room - moved,
     }
 }
 
-fn load_words(game_state: &mut GameState) -> Result<(), Box<dyn Error>> {
-    let asset = Asset::get(
-        format!(
-            "{}_words.txt",
-            game_state.language.to_string().to_lowercase()
-        )
-        .as_str(),
-    )
-    .unwrap();
-    let data = asset.data.as_ref();
-    let words: Vec<String> = std::str::from_utf8(data)?
-        .lines()
-        .map(|line| line.to_string())
-        .filter(|l| !l.is_empty())
-        .collect();
-    game_state.word_pool = words;
-
-    Ok(())
+// Rows taken up by everything but the lanes: margins, borders and the bottom pane
+const LAYOUT_HEIGHT: usize = 7;
+// Columns taken up by the margins and borders beside the lanes
+const LAYOUT_WIDTH: usize = 4;
+// Wide enough for the longest sentence to still move some way across
+pub(crate) const MIN_WIDTH: usize = MAX_SENTENCE_WIDTH + 10 + LAYOUT_WIDTH;
+
+fn lanes_for(height: u16) -> usize {
+    (height as usize).saturating_sub(LAYOUT_HEIGHT)
 }
 
-fn spawn_new_word(game_state: &mut GameState) {
-    if game_state.word_pool.is_empty() {
-        panic!("No more words left.");
+// How far the player is from the end of the game
+fn mode_status(engine: &Engine) -> String {
+    match engine.mode() {
+        Mode::TimeAttack { seconds } => format!(
+            "{}s left",
+            (seconds as u64).saturating_sub(engine.stats().elapsed().as_secs())
+        ),
+        Mode::WordCount { words } => format!(
+            "{} words left",
+            (words as usize).saturating_sub(engine.stats().words)
+        ),
+        mode => mode.to_string(),
     }
+}
 
-    // Get random, open y value
-    let indices: Vec<usize> = game_state
-        .word_slots
-        .iter()
-        .enumerate()
-        .filter(|(_, &val)| val == 0)
-        .map(|(i, _)| i)
-        .collect();
-    if indices.is_empty() {
-        return;
+fn health_color(health: u32, max_health: u32) -> Color {
+    if health * 3 <= max_health {
+        Color::Red
+    } else if health * 3 <= max_health * 2 {
+        Color::Yellow
+    } else {
+        Color::Green
     }
-    let random_index = indices[rand::thread_rng().gen_range(0..indices.len())];
-    game_state.word_slots[random_index] = 1;
-
-    // Get random word from
-    let index = rand::thread_rng().gen_range(0..game_state.word_pool.len());
-    let new_word = game_state.word_pool.remove(index);
-
-    let speed =
-        ((game_state.wpm / FPS as f32 / 20.0) + thread_rng().gen_range(-0.02..0.02)).max(0.01);
-
-    game_state
-        .words
-        .push(Word::new(new_word.to_lowercase(), random_index, speed));
 }
 
-fn generate_display(game_state: &mut GameState, size: Rect) -> Result<bool, String> {
-    game_state.display_rows = vec![];
-    for (i, val) in game_state.word_slots.iter().enumerate() {
-        if val == &0 {
-            game_state
-                .display_rows
-                .push(Spans::from(vec![Span::raw("")]));
-            continue;
-        }
-        let word = game_state
-            .words
-            .iter_mut()
-            .find(|w| w.y == i && !w.found)
-            .unwrap();
+// Words are coloured by progress. The part matching the input is highlighted,
+// and the word the input will capture first is underlined. With lock-on input
+// only the locked word is highlighted. The width is that of the lanes, inside
+// the border.
+fn generate_display(
+    engine: &Engine,
+    width: u16,
+    input: &str,
+    input_mode: InputMode,
+    direction: TextDirection,
+) -> Vec<Spans<'static>> {
+    let target = match input_mode {
+        InputMode::Words => engine.target(input).filter(|_| !input.is_empty()),
+        InputMode::LockOn => engine.locked(),
+    };
+    let mut display_rows = vec![Spans::from(vec![Span::raw("")]); engine.lanes()];
+    for word in engine.words() {
         let text = word.text.to_string();
-        let progress = &word.clone().progress();
-        if progress >= &1.0 {
-            return Ok(false);
-        }
-
+        let progress = word.progress().min(1.0);
         let color = Color::Rgb(
             (progress * 255.0) as u8,
             (255.0 - (progress * 255.0)) as u8,
             0,
         );
-        game_state.display_rows.push(Spans::from(vec![
-            Span::raw(
-                " ".repeat((progress * (size.width as f32 - text.len() as f32 - 3.0)) as usize),
+        let mut style = Style::default().fg(color);
+        let is_target = target.is_some_and(|target| std::ptr::eq(target, word));
+        if is_target {
+            style = style.add_modifier(Modifier::UNDERLINED);
+        }
+        let typed = match input_mode {
+            InputMode::LockOn if !is_target => 0,
+            _ => engine.matching().typed_len(&text, input),
+        };
+        display_rows[word.y] = Spans::from(vec![
+            Span::raw(" ".repeat(word_column(progress, width, &text, direction))),
+            Span::styled(
+                text[..typed].to_string(),
+                style.fg(Color::Cyan).add_modifier(Modifier::BOLD),
             ),
-            Span::styled(text.to_owned(), Style::default().fg(color)),
-        ]));
-        word.increment();
+            Span::styled(text[typed..].to_string(), style),
+        ]);
     }
-    Ok(true)
+    display_rows
 }
 
-fn check_if_typed(game_state: &mut GameState, text: String) -> bool {
-    let mut found = false;
-    game_state
-        .words
-        .iter_mut()
-        .filter(|w| !w.found && w.text.to_uppercase() == text.to_uppercase())
-        .for_each(|w| {
-            found = true;
-            w.found = true;
-            game_state.score += 500.0 * (1.0 - w.clone().progress()).powf(3.0) * w.speed;
-            game_state.word_slots[w.y] = 0;
-        });
-    found
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn words_reach_the_border_by_display_width() {
+        // Hangul and emoji take two columns per character, combining accents none
+        for (text, width) in [("alpha", 5), ("한글", 4), ("cafe\u{301}", 4), ("🚀🚀", 4)] {
+            let column = |progress| word_column(progress, 40, text, TextDirection::Ltr);
+            assert_eq!(column(0.0), 0);
+            assert_eq!(column(1.0) + width, 40, "{}", text);
+            assert_eq!(column(0.5), (40 - width) / 2, "{}", text);
+        }
+        assert_eq!(word_column(1.0, 3, "한글", TextDirection::Ltr), 0);
+    }
+
+    #[test]
+    fn right_to_left_words_move_left() {
+        assert_eq!(word_column(0.0, 40, "שלום", TextDirection::Rtl), 36);
+        assert_eq!(word_column(1.0, 40, "שלום", TextDirection::Rtl), 0);
+    }
 }
@@ -0,0 +1,93 @@
+use tui::{
+    layout::Constraint,
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Cell, Row, Table},
+};
+
+use crate::{
+    difficulty::Difficulty,
+    high_score::{HighScore, TABLE_SIZE},
+    matching::Matching,
+    mode::{InputMode, Mode},
+};
+
+const WIDTHS: [Constraint; 6] = [
+    Constraint::Length(4),
+    Constraint::Length(12),
+    Constraint::Length(8),
+    Constraint::Length(6),
+    Constraint::Length(7),
+    Constraint::Length(10),
+];
+
+// The title of a category, naming the input mode and matching options only when
+// they are not the default
+pub(crate) fn title(
+    language: &str,
+    mode: Mode,
+    difficulty: Difficulty,
+    input_mode: InputMode,
+    matching: Matching,
+    punctuate: bool,
+) -> String {
+    let mut parts = vec![
+        language.to_string(),
+        mode.to_string(),
+        difficulty.to_string(),
+    ];
+    if input_mode != InputMode::default() {
+        parts.push(input_mode.to_string());
+    }
+    parts.extend(options(matching, punctuate).iter().map(|o| o.to_string()));
+    format!("High scores ({}):", parts.join(", "))
+}
+
+// The matching options that are not the default, which have their own categories
+pub(crate) fn options(matching: Matching, punctuate: bool) -> Vec<&'static str> {
+    [
+        (matching.strict, "strict"),
+        (punctuate, "punctuated"),
+        (matching.jamo, "jamo"),
+    ]
+    .into_iter()
+    .filter_map(|(set, name)| set.then_some(name))
+    .collect()
+}
+
+// Build the top entries of a high-score category, optionally highlighting one rank
+pub(crate) fn build<'a>(
+    title: String,
+    entries: &[&HighScore],
+    highlight: Option<usize>,
+) -> Table<'a> {
+    let header = Row::new(vec!["#", "Name", "Score", "WPM", "Time", "Date"])
+        .style(Style::default().add_modifier(Modifier::BOLD));
+    let rows: Vec<Row> = entries
+        .iter()
+        .take(TABLE_SIZE)
+        .enumerate()
+        .map(|(i, entry)| {
+            let style = if Some(i) == highlight {
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            Row::new(vec![
+                Cell::from(format!("{}", i + 1)),
+                Cell::from(entry.name.to_owned()),
+                Cell::from(format!("{:.1}", entry.score)),
+                Cell::from(format!("{:.1}", entry.wpm)),
+                Cell::from(format!("{:.1}s", entry.seconds)),
+                Cell::from(entry.date.to_string()),
+            ])
+            .style(style)
+        })
+        .collect();
+
+    Table::new(rows)
+        .header(header)
+        .block(Block::default().title(title).borders(Borders::NONE))
+        .widths(&WIDTHS)
+}
@@ -1,70 +1,99 @@
-use std::{
-    error::Error,
-    io::Stdout,
-    thread,
-    time::{Duration, Instant},
-};
+use std::{error::Error, time::Duration};
 
-use crossterm::event::{self, Event, KeyCode};
-use strum::IntoEnumIterator;
+use crossterm::event::{Event, KeyCode};
 use tui::{
-    backend::CrosstermBackend,
-    layout::{Alignment, Constraint, Direction, Layout, Rect},
-    text::Spans,
+    backend::Backend,
+    layout::{Alignment, Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
     widgets::{Block, BorderType, Borders, List, ListItem, Paragraph, Wrap},
     Terminal,
 };
 
-use crate::{GameError, GameState, Language, StatefulList, FRAME_TIME};
+use crate::{
+    difficulty::Difficulty,
+    game::{events::EventSource, high_score_table, Screen},
+    high_score::HighScores,
+    language_pack,
+    matching::Matching,
+    mode::{InputMode, Mode},
+    word_list::{self, WordList},
+    GameState, StatefulList,
+};
 
-pub(crate) fn show_view(
-    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+pub(crate) fn show_view<B: Backend>(
+    terminal: &mut Terminal<B>,
+    events: &mut impl EventSource,
     game_state: &mut GameState,
-) -> Result<bool, Box<dyn Error>> {
-    let mut last_frame_time = Instant::now();
+    word_lists: &[WordList],
+    high_scores: &HighScores,
+) -> Result<Screen, Box<dyn Error>> {
+    let mut error_message = high_scores
+        .file
+        .error
+        .clone()
+        .or_else(|| game_state.config.error.clone());
 
-    // Create a list of options
-    let languages: Vec<Language> = Language::iter().collect();
+    // Create the lists of word lists, modes, difficulties and input modes
     let mut items = StatefulList::with_items(
-        languages
+        word_lists
             .iter()
             .map(|l| ListItem::new(l.to_string()))
             .collect(),
     );
-    items.next();
-
-    // Create the list widget and set its items
-    let list = List::new(&*items.items)
-        .block(
-            tui::widgets::Block::default()
-                .title("Select your language of choice:")
-                .borders(tui::widgets::Borders::NONE),
-        )
-        .highlight_symbol(">> ");
+    items.state.select(
+        word_lists
+            .iter()
+            .position(|l| l == &game_state.word_list)
+            .or(Some(0)),
+    );
+    let mut modes = Mode::presets();
+    if !modes.contains(&game_state.mode) {
+        modes.push(game_state.mode);
+    }
+    let mut mode_items =
+        StatefulList::with_items(modes.iter().map(|m| ListItem::new(m.to_string())).collect());
+    mode_items
+        .state
+        .select(modes.iter().position(|m| m == &game_state.mode));
+    let difficulties = Difficulty::options(game_state.config.custom_difficulty.as_ref());
+    let mut difficulty_items = StatefulList::with_items(
+        difficulties
+            .iter()
+            .map(|d| ListItem::new(d.to_string()))
+            .collect(),
+    );
+    difficulty_items.state.select(
+        difficulties
+            .iter()
+            .position(|d| d == &game_state.difficulty)
+            .or(Some(1)),
+    );
+    let input_modes = [InputMode::Words, InputMode::LockOn];
+    let mut input_mode_items = StatefulList::with_items(
+        input_modes
+            .iter()
+            .map(|i| ListItem::new(i.to_string()))
+            .collect(),
+    );
+    input_mode_items
+        .state
+        .select(input_modes.iter().position(|i| i == &game_state.input_mode));
+    // Which of the lists the arrow keys move in
+    let mut focus = 0;
 
     loop {
-        let elapsed_time = last_frame_time.elapsed();
-        last_frame_time = Instant::now();
+        events.next_frame();
 
         // Get the size of the terminal
         let size = terminal.size()?;
-        if size.height < 47 {
-            return Err(Box::new(GameError(
-                "Console should be at least 47 lines tall",
-            )));
-        }
 
         // Create a layouts and widgets
         let main_pane = Layout::default()
             .direction(Direction::Vertical)
             .margin(1)
             .constraints([Constraint::Min(0)].as_ref())
-            .split(Rect {
-                x: 0,
-                y: 0,
-                width: size.width,
-                height: 47,
-            });
+            .split(size);
         let block = Block::default()
             .borders(Borders::ALL)
             .title(" Type Defender ")
@@ -73,7 +102,14 @@ pub(crate) fn show_view(
         let inner_pane = Layout::default()
             .direction(Direction::Vertical)
             .margin(3)
-            .constraints([Constraint::Min(11), Constraint::Percentage(100)].as_ref())
+            .constraints(
+                [
+                    Constraint::Min(13),
+                    Constraint::Length(2),
+                    Constraint::Percentage(100),
+                ]
+                .as_ref(),
+            )
             .split(main_pane[0]);
         let help_text = vec![
             Spans::from(""),
@@ -81,48 +117,171 @@ pub(crate) fn show_view(
             Spans::from("Type out the moving words before they reach the edge of the terminal."),
             Spans::from(""),
             Spans::from("Controls:"),
-            Spans::from(" - Esc:   Quit gme"),
-            Spans::from(" - Enter: Clear text input"),
+            Spans::from(" - Esc:   Pause game (or Ctrl+P)"),
+            Spans::from(" - Enter: Clear mistyped input"),
+            Spans::from(" - Tab:   Switch between word lists, modes, difficulties and input"),
+            Spans::from(" - S:     Show which keys you miss most"),
             Spans::from(""),
             Spans::from(
                 "Note: For complex character like in 한글, please press Enter, Right-Arrow, or \
-                    Space to complete a word.",
+                    Space to complete a word. Without an IME, start with --jamo to type the \
+                    letters one by one.",
             ),
+            Spans::from(""),
+            Spans::from(format!(
+                "Custom word lists (*.txt) are loaded from {}, and language packs from {}",
+                word_list::config_dir()
+                    .map(|dir| dir.display().to_string())
+                    .unwrap_or_else(|| "the --words option".to_string()),
+                language_pack::config_dir()
+                    .map(|dir| dir.display().to_string())
+                    .unwrap_or_else(|| "nowhere".to_string())
+            )),
         ];
         let help_paragraph = Paragraph::new(help_text).wrap(Wrap { trim: true });
+        let list_pane = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(
+                [
+                    Constraint::Percentage(20),
+                    Constraint::Percentage(20),
+                    Constraint::Percentage(12),
+                    Constraint::Percentage(48),
+                ]
+                .as_ref(),
+            )
+            .split(inner_pane[2]);
+        let setting_pane = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(
+                [
+                    Constraint::Length(difficulties.len() as u16 + 2),
+                    Constraint::Min(0),
+                ]
+                .as_ref(),
+            )
+            .split(list_pane[2]);
+        let list = selector("Select your word list:", &items.items, focus == 0);
+        let mode_list = selector("Select your mode:", &mode_items.items, focus == 1);
+        let difficulty_list = selector("Difficulty:", &difficulty_items.items, focus == 2);
+        let input_mode_list = selector("Input:", &input_mode_items.items, focus == 3);
+        let selected_list = word_lists[items.state.selected().unwrap()].to_string();
+        let selected_mode = modes[mode_items.state.selected().unwrap()];
+        let selected_difficulty = difficulties[difficulty_items.state.selected().unwrap()];
+        let selected_input_mode = input_modes[input_mode_items.state.selected().unwrap()];
+        let selected_matching =
+            game_state.matching_for(&word_lists[items.state.selected().unwrap()]);
+        let category = high_scores.category(
+            &selected_list,
+            selected_mode,
+            selected_difficulty,
+            selected_input_mode,
+            selected_matching,
+            game_state.punctuate,
+        );
+        let table = high_score_table::build(
+            high_score_table::title(
+                &selected_list,
+                selected_mode,
+                selected_difficulty,
+                selected_input_mode,
+                selected_matching,
+                game_state.punctuate,
+            ),
+            &category,
+            None,
+        );
+        // Errors take the place of the details of the selected word list
+        let error_paragraph = Paragraph::new(match &error_message {
+            Some(error) => Span::styled(error.to_owned(), Style::default().fg(Color::Red)),
+            None => Span::raw(describe(
+                &word_lists[items.state.selected().unwrap()],
+                selected_matching,
+                game_state.punctuate,
+            )),
+        })
+        .wrap(Wrap { trim: true });
 
         // Render terminal
         terminal.draw(|f| {
             f.render_widget(block, main_pane[0]);
             f.render_widget(help_paragraph, inner_pane[0]);
-            f.render_widget(list.clone(), inner_pane[1]);
-            f.render_stateful_widget(list.clone(), inner_pane[1], &mut items.state)
+            f.render_widget(error_paragraph, inner_pane[1]);
+            f.render_stateful_widget(list, list_pane[0], &mut items.state);
+            f.render_stateful_widget(mode_list, list_pane[1], &mut mode_items.state);
+            f.render_stateful_widget(
+                difficulty_list,
+                setting_pane[0],
+                &mut difficulty_items.state,
+            );
+            f.render_stateful_widget(
+                input_mode_list,
+                setting_pane[1],
+                &mut input_mode_items.state,
+            );
+            f.render_widget(table, list_pane[3])
         })?;
 
         // Wait for a key press event
-        if event::poll(Duration::from_millis(33))? {
-            if let Event::Key(key) = event::read()? {
-                match key.code {
-                    KeyCode::Esc => return Ok(false),
-                    KeyCode::Down => items.next(),
-                    KeyCode::Up => items.previous(),
-                    KeyCode::Enter => {
-                        game_state.language = languages
-                            .get(items.state.selected().unwrap())
-                            .unwrap()
-                            .clone();
-                        break;
+        if let Some(Event::Key(key)) = events.poll(Duration::from_millis(33))? {
+            match key.code {
+                KeyCode::Esc => return Ok(Screen::Quit),
+                KeyCode::Char('s') => return Ok(Screen::Stats),
+                KeyCode::Right | KeyCode::Tab => focus = (focus + 1) % 4,
+                KeyCode::Left | KeyCode::BackTab => focus = (focus + 3) % 4,
+                KeyCode::Down => match focus {
+                    0 => items.next(),
+                    1 => mode_items.next(),
+                    2 => difficulty_items.next(),
+                    _ => input_mode_items.next(),
+                },
+                KeyCode::Up => match focus {
+                    0 => items.previous(),
+                    1 => mode_items.previous(),
+                    2 => difficulty_items.previous(),
+                    _ => input_mode_items.previous(),
+                },
+                KeyCode::Enter => {
+                    let word_list = word_lists.get(items.state.selected().unwrap()).unwrap();
+                    match word_list.load() {
+                        Ok(words) => {
+                            game_state.word_list = word_list.clone();
+                            game_state.mode = selected_mode;
+                            game_state.difficulty = selected_difficulty;
+                            game_state.input_mode = selected_input_mode;
+                            game_state.word_pool = words;
+                            break;
+                        }
+                        Err(error) => error_message = Some(error.to_string()),
                     }
-                    _ => {}
                 }
+                _ => {}
             }
         }
+    }
+    Ok(Screen::Game)
+}
+
+// A list to pick from, with its title highlighted while the arrow keys move in it
+fn selector<'a>(title: &'a str, items: &[ListItem<'a>], focused: bool) -> List<'a> {
+    let title_style = if focused {
+        Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default()
+    };
+    List::new(items.to_vec())
+        .block(Block::default().title(Span::styled(title, title_style)))
+        .highlight_symbol(">> ")
+}
 
-        // Sleep to maintain desired FPS
-        let time_to_sleep = FRAME_TIME
-            .checked_sub(elapsed_time)
-            .unwrap_or_else(|| Duration::from_micros(0));
-        thread::sleep(time_to_sleep);
+// The details of a word list, and the matching options its scores are kept under
+fn describe(word_list: &WordList, matching: Matching, punctuate: bool) -> String {
+    let options = high_score_table::options(matching, punctuate);
+    if options.is_empty() {
+        word_list.describe()
+    } else {
+        format!("{}, matching: {}", word_list.describe(), options.join(", "))
     }
-    Ok(true)
 }
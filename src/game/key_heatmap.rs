@@ -0,0 +1,73 @@
+use tui::{
+    style::{Color, Style},
+    text::{Span, Spans},
+};
+
+use crate::key_stats::KeyStats;
+
+const ROWS: [&str; 3] = ["qwertyuiop", "asdfghjkl", "zxcvbnm"];
+// Miss rate at which a key shows as fully red
+const WORST_MISS_RATE: f32 = 0.25;
+
+// A keyboard with each key coloured by its miss rate, followed by the most
+// missed keys (including ones not on the keyboard) and the slowest bigrams
+pub(crate) fn build(key_stats: &KeyStats) -> Vec<Spans<'static>> {
+    let mut lines: Vec<Spans> = ROWS
+        .iter()
+        .enumerate()
+        .map(|(indent, row)| {
+            let mut spans = vec![Span::raw(" ".repeat(indent * 2))];
+            for key in row.chars() {
+                spans.push(Span::styled(
+                    format!(" {} ", key),
+                    key_style(key_stats, key),
+                ));
+                spans.push(Span::raw(" "));
+            }
+            Spans::from(spans)
+        })
+        .collect();
+
+    let most_missed: Vec<String> = key_stats
+        .most_missed(8)
+        .iter()
+        .map(|(key, rate)| format!("{} {:.0}%", key, rate * 100.0))
+        .collect();
+    lines.push(Spans::from(format!(
+        "Most missed: {}",
+        if most_missed.is_empty() {
+            "none".to_string()
+        } else {
+            most_missed.join(", ")
+        }
+    )));
+
+    let slowest: Vec<String> = key_stats
+        .slowest_bigrams(6)
+        .iter()
+        .map(|(bigram, time)| format!("'{}' {}ms", bigram, time.as_millis()))
+        .collect();
+    lines.push(Spans::from(format!(
+        "Slowest bigrams: {}",
+        if slowest.is_empty() {
+            "not enough typing yet".to_string()
+        } else {
+            slowest.join(", ")
+        }
+    )));
+    lines
+}
+
+fn key_style(key_stats: &KeyStats, key: char) -> Style {
+    match key_stats.key(key) {
+        None => Style::default().fg(Color::DarkGray),
+        Some(count) => {
+            let heat = (count.miss_rate() / WORST_MISS_RATE).min(1.0);
+            Style::default().fg(Color::Black).bg(Color::Rgb(
+                (heat * 255.0) as u8,
+                (255.0 - heat * 255.0) as u8,
+                0,
+            ))
+        }
+    }
+}
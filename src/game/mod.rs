@@ -1,3 +1,21 @@
 pub mod end_screen;
+pub mod events;
 pub mod game_screen;
+pub mod high_score_table;
 pub mod home_screen;
+pub mod key_heatmap;
+pub mod pause_menu;
+pub mod stats_screen;
+
+// The screen to show next
+#[derive(Debug, PartialEq)]
+pub(crate) enum Screen {
+    Home,
+    Game,
+    End,
+    Stats,
+    Quit,
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,130 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use tui::{
+    backend::Backend,
+    layout::{Alignment, Rect},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem},
+    Frame,
+};
+
+use crate::StatefulList;
+
+pub(crate) enum PauseAction {
+    Resume,
+    Restart,
+    EndGame,
+    QuitToMenu,
+    TogglePauseOnFocusLoss,
+}
+
+pub(crate) fn is_pause_key(key: &KeyEvent) -> bool {
+    key.code == KeyCode::Esc
+        || (key.code == KeyCode::Char('p') && key.modifiers.contains(KeyModifiers::CONTROL))
+}
+
+// Ctrl or Alt with a letter edits the input instead of typing it. AltGr comes as
+// both on some systems and still types a letter.
+pub(crate) fn is_command_key(key: &KeyEvent) -> bool {
+    key.modifiers.contains(KeyModifiers::CONTROL) != key.modifiers.contains(KeyModifiers::ALT)
+}
+
+pub(crate) struct PauseMenu {
+    options: StatefulList<&'static str>,
+    settings: Option<StatefulList<&'static str>>,
+}
+
+impl PauseMenu {
+    pub(crate) fn new() -> Self {
+        let mut options = StatefulList::with_items(vec![
+            "Resume",
+            "Restart",
+            "Settings",
+            "End game",
+            "Quit to menu",
+        ]);
+        options.next();
+        PauseMenu {
+            options,
+            settings: None,
+        }
+    }
+
+    pub(crate) fn handle_key(&mut self, key: KeyEvent) -> Option<PauseAction> {
+        if let Some(settings) = &mut self.settings {
+            match key.code {
+                KeyCode::Esc => self.settings = None,
+                KeyCode::Down => settings.next(),
+                KeyCode::Up => settings.previous(),
+                KeyCode::Enter => match settings.items[settings.state.selected().unwrap()] {
+                    "Pause when focus is lost" => return Some(PauseAction::TogglePauseOnFocusLoss),
+                    _ => self.settings = None,
+                },
+                _ => {}
+            }
+            return None;
+        }
+
+        match key.code {
+            _ if is_pause_key(&key) => return Some(PauseAction::Resume),
+            KeyCode::Down => self.options.next(),
+            KeyCode::Up => self.options.previous(),
+            KeyCode::Enter => match self.options.items[self.options.state.selected().unwrap()] {
+                "Resume" => return Some(PauseAction::Resume),
+                "Restart" => return Some(PauseAction::Restart),
+                "End game" => return Some(PauseAction::EndGame),
+                "Quit to menu" => return Some(PauseAction::QuitToMenu),
+                _ => {
+                    let mut settings =
+                        StatefulList::with_items(vec!["Pause when focus is lost", "Back"]);
+                    settings.next();
+                    self.settings = Some(settings);
+                }
+            },
+            _ => {}
+        }
+        None
+    }
+
+    pub(crate) fn render<B: Backend>(
+        &mut self,
+        f: &mut Frame<B>,
+        area: Rect,
+        pause_on_focus_loss: bool,
+    ) {
+        let (title, list) = match &mut self.settings {
+            Some(settings) => ("Settings", settings),
+            None => ("Paused", &mut self.options),
+        };
+        let items: Vec<ListItem> = list
+            .items
+            .iter()
+            .map(|&item| match item {
+                "Pause when focus is lost" => ListItem::new(format!(
+                    "{}: {}",
+                    item,
+                    if pause_on_focus_loss { "On" } else { "Off" }
+                )),
+                _ => ListItem::new(item),
+            })
+            .collect();
+
+        let width = 40.min(area.width);
+        let height = (list.items.len() as u16 + 2).min(area.height);
+        let popup = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+        let menu = List::new(items)
+            .block(
+                Block::default()
+                    .title(format!(" {} ", title))
+                    .title_alignment(Alignment::Center)
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded),
+            )
+            .highlight_symbol(">> ");
+        f.render_widget(Clear, popup);
+        f.render_stateful_widget(menu, popup, &mut list.state);
+    }
+}
@@ -0,0 +1,78 @@
+use std::{error::Error, time::Duration};
+
+use crossterm::event::{Event, KeyCode};
+use tui::{
+    backend::Backend,
+    layout::{Alignment, Constraint, Direction, Layout},
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, BorderType, Borders, Paragraph, Wrap},
+    Terminal,
+};
+
+use crate::{
+    game::{events::EventSource, key_heatmap, Screen},
+    key_stats::KeyStats,
+};
+
+pub(crate) fn show_view<B: Backend>(
+    terminal: &mut Terminal<B>,
+    events: &mut impl EventSource,
+    key_stats: &KeyStats,
+) -> Result<Screen, Box<dyn Error>> {
+    loop {
+        events.next_frame();
+
+        // Get the size of the terminal
+        let size = terminal.size()?;
+
+        // Create a layouts and widgets
+        let main_pane = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(1)
+            .constraints([Constraint::Min(0)].as_ref())
+            .split(size);
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(" Type Defender ")
+            .title_alignment(Alignment::Center)
+            .border_type(BorderType::Rounded);
+        let inner_pane = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(3)
+            .constraints([Constraint::Min(0)].as_ref())
+            .split(main_pane[0]);
+
+        let mut text = vec![
+            Spans::from("Key stats over all your games"),
+            Spans::from(""),
+        ];
+        if key_stats.is_empty() {
+            text.push(Spans::from(
+                "Nothing recorded yet. Play a game to see which keys to practise.",
+            ));
+        } else {
+            text.extend(key_heatmap::build(key_stats));
+        }
+        text.push(Spans::from(""));
+        text.push(Spans::from(Span::styled(
+            key_stats.file.error.clone().unwrap_or_default(),
+            Style::default().fg(Color::Red),
+        )));
+        text.push(Spans::from("Press Esc or Enter to go back."));
+        let paragraph = Paragraph::new(text).wrap(Wrap { trim: false });
+
+        // Render terminal
+        terminal.draw(|f| {
+            f.render_widget(block, main_pane[0]);
+            f.render_widget(paragraph, inner_pane[0]);
+        })?;
+
+        // Wait for a key press event
+        if let Some(Event::Key(key)) = events.poll(Duration::from_millis(33))? {
+            if matches!(key.code, KeyCode::Esc | KeyCode::Enter) {
+                return Ok(Screen::Home);
+            }
+        }
+    }
+}
@@ -0,0 +1,666 @@
+use std::{collections::VecDeque, io, path::PathBuf, time::Duration};
+
+use clap::Parser;
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use tui::{
+    backend::TestBackend,
+    style::{Color, Modifier},
+    Terminal,
+};
+
+use crate::{
+    cli::Args,
+    difficulty::Difficulty,
+    game::{end_screen, events::EventSource, game_screen, home_screen, stats_screen, Screen},
+    high_score::HighScores,
+    key_stats::KeyStats,
+    language_pack,
+    mode::{InputMode, Mode},
+    word_list::{self, WordList, MAX_SENTENCE_WIDTH},
+    GameState, FRAME_TIME,
+};
+
+const MAX_FRAMES: usize = 20_000;
+
+// Replays a script of key presses, one per frame, then idles
+struct ScriptedEvents {
+    script: VecDeque<Option<Event>>,
+    frames: usize,
+}
+
+impl ScriptedEvents {
+    fn new() -> Self {
+        ScriptedEvents {
+            script: VecDeque::new(),
+            frames: 0,
+        }
+    }
+
+    fn event(mut self, event: Event) -> Self {
+        self.script.push_back(Some(event));
+        self
+    }
+
+    fn key(self, code: KeyCode) -> Self {
+        self.event(Event::Key(KeyEvent::new(code, KeyModifiers::NONE)))
+    }
+
+    fn ctrl(self, c: char) -> Self {
+        self.event(Event::Key(KeyEvent::new(
+            KeyCode::Char(c),
+            KeyModifiers::CONTROL,
+        )))
+    }
+
+    fn text(mut self, text: &str) -> Self {
+        for c in text.chars() {
+            self = self.key(KeyCode::Char(c));
+        }
+        self
+    }
+
+    fn wait(mut self, duration: Duration) -> Self {
+        let frames = duration.as_micros() / FRAME_TIME.as_micros();
+        for _ in 0..frames {
+            self.script.push_back(None);
+        }
+        self
+    }
+}
+
+impl EventSource for ScriptedEvents {
+    fn poll(&mut self, _timeout: Duration) -> io::Result<Option<Event>> {
+        Ok(self.script.pop_front().flatten())
+    }
+
+    fn next_frame(&mut self) -> Duration {
+        self.frames += 1;
+        assert!(self.frames < MAX_FRAMES, "screen did not finish");
+        FRAME_TIME
+    }
+}
+
+fn terminal() -> Terminal<TestBackend> {
+    Terminal::new(TestBackend::new(120, 47)).unwrap()
+}
+
+fn screen_text(terminal: &Terminal<TestBackend>) -> String {
+    let buffer = terminal.backend().buffer();
+    let mut text = String::new();
+    for y in 0..buffer.area.height {
+        for x in 0..buffer.area.width {
+            text.push_str(&buffer.get(x, y).symbol);
+        }
+        text.push('\n');
+    }
+    text
+}
+
+fn game_state() -> GameState {
+    let mut game_state = GameState::new(&Args::parse_from(["type_defender", "--seed", "1"]));
+    game_state.player_name = "tester".to_string();
+    game_state
+}
+
+// Press the keys on the home screen, which must end by starting a game
+fn select_on_home_screen(
+    mut events: ScriptedEvents,
+    game_state: &mut GameState,
+) -> Terminal<TestBackend> {
+    let mut terminal = terminal();
+    let screen = home_screen::show_view(
+        &mut terminal,
+        &mut events,
+        game_state,
+        &word_list::discover(&[], false),
+        &HighScores::default(),
+    )
+    .unwrap();
+    assert_eq!(screen, Screen::Game);
+    terminal
+}
+
+#[test]
+fn home_screen_selects_word_list() {
+    // English sentences come between English and German
+    let events = ScriptedEvents::new()
+        .key(KeyCode::Down)
+        .key(KeyCode::Down)
+        .key(KeyCode::Enter);
+    let mut game_state = game_state();
+
+    let terminal = select_on_home_screen(events, &mut game_state);
+
+    assert_eq!(game_state.word_list.to_string(), "German");
+    assert!(!game_state.word_pool.is_empty());
+    let text = screen_text(&terminal);
+    assert!(text.contains("Select your word list:"));
+    assert!(text.contains(">> German"));
+    assert!(text.contains("German: Latin script, keyboard: QWERTZ"));
+}
+
+#[test]
+fn home_screen_selects_sentences() {
+    let events = ScriptedEvents::new().key(KeyCode::Down).key(KeyCode::Enter);
+    let mut game_state = game_state();
+
+    let terminal = select_on_home_screen(events, &mut game_state);
+
+    assert_eq!(game_state.word_list.to_string(), "English sentences");
+    assert!(game_state.word_pool.iter().any(|s| s.ends_with('.')));
+    let text = screen_text(&terminal);
+    assert!(text.contains(">> English sentences"));
+    assert!(text.contains("(sentences, typed with capitals and punctuation), matching: strict"));
+}
+
+#[test]
+fn home_screen_shows_matching_options() {
+    let events = ScriptedEvents::new().key(KeyCode::Enter);
+    let mut game_state = GameState::new(&Args::parse_from([
+        "type_defender",
+        "--seed",
+        "1",
+        "--punctuate",
+        "--jamo",
+    ]));
+
+    let terminal = select_on_home_screen(events, &mut game_state);
+
+    assert!(screen_text(&terminal).contains("matching: strict, punctuated, jamo"));
+}
+
+#[test]
+fn home_screen_selects_mode() {
+    let events = ScriptedEvents::new()
+        .key(KeyCode::Tab)
+        .key(KeyCode::Down)
+        .key(KeyCode::Enter);
+    let mut game_state = game_state();
+
+    let terminal = select_on_home_screen(events, &mut game_state);
+
+    assert_eq!(game_state.word_list.to_string(), "English");
+    assert_eq!(game_state.mode, Mode::TimeAttack { seconds: 60 });
+    assert!(screen_text(&terminal).contains(">> Time attack (60s)"));
+}
+
+#[test]
+fn home_screen_selects_difficulty() {
+    let events = ScriptedEvents::new()
+        .key(KeyCode::Left)
+        .key(KeyCode::Left)
+        .key(KeyCode::Down)
+        .key(KeyCode::Enter);
+    let mut game_state = game_state();
+
+    let terminal = select_on_home_screen(events, &mut game_state);
+
+    assert_eq!(game_state.difficulty, Difficulty::Hard);
+    assert!(screen_text(&terminal).contains(">> Hard"));
+}
+
+#[test]
+fn home_screen_selects_input_mode() {
+    let events = ScriptedEvents::new()
+        .key(KeyCode::BackTab)
+        .key(KeyCode::Down)
+        .key(KeyCode::Enter);
+    let mut game_state = game_state();
+
+    let terminal = select_on_home_screen(events, &mut game_state);
+
+    assert_eq!(game_state.input_mode, InputMode::LockOn);
+    let text = screen_text(&terminal);
+    assert!(text.contains(">> Lock-on"));
+    assert!(text.contains("Normal, Lock-on):"));
+}
+
+#[test]
+fn home_screen_reports_missing_word_list() {
+    let mut terminal = terminal();
+    let mut events = ScriptedEvents::new()
+        .key(KeyCode::Enter)
+        .wait(FRAME_TIME)
+        .key(KeyCode::Esc);
+    let mut game_state = game_state();
+    let word_lists = vec![WordList::from_path(
+        &PathBuf::from("/missing/words.txt"),
+        false,
+    )];
+    game_state.word_list = word_lists[0].clone();
+
+    let screen = home_screen::show_view(
+        &mut terminal,
+        &mut events,
+        &mut game_state,
+        &word_lists,
+        &HighScores::default(),
+    )
+    .unwrap();
+
+    assert_eq!(screen, Screen::Quit);
+    assert!(screen_text(&terminal).contains("Could not read word list '/missing/words.txt'"));
+}
+
+#[test]
+fn game_screen_plays_until_a_word_leaks() {
+    let mut terminal = terminal();
+    let mut events = ScriptedEvents::new()
+        .wait(Duration::from_secs(1))
+        .text("alpha");
+    let mut game_state = game_state();
+    game_state.word_pool = vec!["alpha".to_string(); 200];
+
+    let screen = game_screen::show_view(&mut terminal, &mut events, &mut game_state).unwrap();
+
+    assert_eq!(screen, Screen::End);
+    assert!(game_state.engine.is_over());
+    assert_eq!(game_state.engine.stats().words, 1);
+    assert!(game_state.engine.score() > 0.0);
+    let text = screen_text(&terminal);
+    assert!(text.contains("Score"));
+    assert!(text.contains("x1"));
+    assert!(text.contains("alpha"));
+}
+
+#[test]
+fn zen_game_ends_from_the_pause_menu() {
+    let mut terminal = terminal();
+    let mut events = ScriptedEvents::new()
+        .wait(Duration::from_secs(90))
+        .key(KeyCode::Esc)
+        .key(KeyCode::Up)
+        .key(KeyCode::Up)
+        .key(KeyCode::Enter);
+    let mut game_state = game_state();
+    game_state.mode = Mode::Zen;
+    game_state.word_pool = vec!["alpha".to_string(); 200];
+
+    let screen = game_screen::show_view(&mut terminal, &mut events, &mut game_state).unwrap();
+
+    assert_eq!(screen, Screen::End);
+    assert!(game_state.engine.leaked() > 0);
+    assert!(screen_text(&terminal).contains(">> End game"));
+}
+
+#[test]
+fn lives_game_shows_a_health_bar() {
+    let mut terminal = terminal();
+    let mut events = ScriptedEvents::new();
+    let mut game_state = game_state();
+    game_state.mode = Mode::Lives { lives: 2, regen: 0 };
+    game_state.word_pool = vec!["alpha".to_string(); 200];
+
+    let screen = game_screen::show_view(&mut terminal, &mut events, &mut game_state).unwrap();
+
+    assert_eq!(screen, Screen::End);
+    assert_eq!(game_state.engine.leaked(), 2);
+    assert_eq!(game_state.engine.health(), Some((0, 2)));
+    let text = screen_text(&terminal);
+    assert!(text.contains("Health"));
+    assert!(text.contains("1/2"));
+}
+
+#[test]
+fn practice_game_explains_its_focus() {
+    let mut terminal = terminal();
+    let mut events = ScriptedEvents::new()
+        .wait(Duration::from_secs(1))
+        .key(KeyCode::Esc)
+        .key(KeyCode::Up)
+        .key(KeyCode::Enter);
+    let mut game_state = game_state();
+    game_state.mode = Mode::Practice;
+    game_state.key_history.record_key('q', false);
+    game_state.word_pool = vec!["quiz".to_string(); 200];
+
+    let screen = game_screen::show_view(&mut terminal, &mut events, &mut game_state).unwrap();
+
+    assert_eq!(screen, Screen::Home);
+    assert!(screen_text(&terminal).contains("Focusing on: q"));
+}
+
+#[test]
+fn lock_on_game_shoots_letters_off_words() {
+    let mut terminal = terminal();
+    let mut events = ScriptedEvents::new()
+        .wait(Duration::from_secs(3))
+        .text("alpxha")
+        .text("al")
+        .ctrl('p')
+        .key(KeyCode::Enter)
+        .ctrl('a')
+        .key(KeyCode::Backspace)
+        .text("a")
+        .key(KeyCode::Esc)
+        .key(KeyCode::Esc)
+        .key(KeyCode::Up)
+        .key(KeyCode::Enter);
+    let mut game_state = game_state();
+    game_state.input_mode = InputMode::LockOn;
+    game_state.word_pool = vec!["alpha".to_string(); 200];
+
+    let screen = game_screen::show_view(&mut terminal, &mut events, &mut game_state).unwrap();
+
+    // Ctrl+A is not typed, Backspace releases the lock, the first Esc only
+    // releases the lock again and the second one pauses
+    assert_eq!(screen, Screen::Home);
+    assert_eq!(game_state.engine.stats().words, 1);
+    assert_eq!(game_state.engine.stats().typed, 9);
+    assert_eq!(game_state.engine.stats().correct, 8);
+    assert_eq!(game_state.engine.stats().backspaces, 1);
+    assert!(game_state.engine.locked().is_none());
+    assert_eq!(game_state.engine.key_stats().key('h').unwrap().misses, 1);
+}
+
+#[test]
+fn enter_only_clears_mistyped_input() {
+    let mut terminal = terminal();
+    let mut events = ScriptedEvents::new()
+        .wait(Duration::from_secs(1))
+        .text("al")
+        .key(KeyCode::Enter)
+        .text("pha")
+        .wait(Duration::from_secs(2))
+        .text("bx")
+        .key(KeyCode::Enter)
+        .text("alpha")
+        .key(KeyCode::Esc)
+        .key(KeyCode::Up)
+        .key(KeyCode::Enter);
+    let mut game_state = game_state();
+    game_state.word_pool = vec!["alpha".to_string(); 200];
+
+    let screen = game_screen::show_view(&mut terminal, &mut events, &mut game_state).unwrap();
+
+    assert_eq!(screen, Screen::Home);
+    assert_eq!(game_state.engine.stats().words, 2);
+}
+
+#[test]
+fn control_keys_edit_the_input_without_typing() {
+    let mut terminal = terminal();
+    let mut events = ScriptedEvents::new()
+        .wait(Duration::from_secs(1))
+        .text("alx")
+        .ctrl('w')
+        .text("alpha")
+        .key(KeyCode::Esc)
+        .key(KeyCode::Up)
+        .key(KeyCode::Enter);
+    let mut game_state = game_state();
+    game_state.word_pool = vec!["alpha".to_string(); 200];
+
+    let screen = game_screen::show_view(&mut terminal, &mut events, &mut game_state).unwrap();
+
+    assert_eq!(screen, Screen::Home);
+    assert_eq!(game_state.engine.stats().words, 1);
+    assert_eq!(game_state.engine.stats().typed, 8);
+    assert_eq!(game_state.engine.stats().correct, 7);
+}
+
+#[test]
+fn sentence_game_types_spaces_and_capitals() {
+    let mut terminal = terminal();
+    let mut events = ScriptedEvents::new()
+        .wait(Duration::from_secs(1))
+        .text(" hi")
+        .key(KeyCode::Enter)
+        .text("Hi there, you.")
+        .key(KeyCode::Esc)
+        .key(KeyCode::Up)
+        .key(KeyCode::Enter);
+    let mut game_state = game_state();
+    game_state.word_list = WordList::Sentences(language_pack::find("english").unwrap());
+    game_state.word_pool = vec!["Hi there, you.".to_string(); 200];
+
+    let screen = game_screen::show_view(&mut terminal, &mut events, &mut game_state).unwrap();
+
+    // The space before any word is dropped, and the lower case h is a mistake
+    assert_eq!(screen, Screen::Home);
+    assert_eq!(game_state.engine.stats().words, 1);
+    assert_eq!(game_state.engine.stats().typed, 16);
+    assert_eq!(game_state.engine.stats().correct, 14);
+}
+
+fn play_and_pause(input: &str) -> Terminal<TestBackend> {
+    let mut terminal = terminal();
+    let mut events = ScriptedEvents::new()
+        .wait(Duration::from_secs(3))
+        .text(input)
+        .wait(FRAME_TIME)
+        .key(KeyCode::Esc)
+        .key(KeyCode::Up)
+        .key(KeyCode::Enter);
+    let mut game_state = game_state();
+    game_state.word_pool = vec!["alpha".to_string(); 200];
+    game_screen::show_view(&mut terminal, &mut events, &mut game_state).unwrap();
+    terminal
+}
+
+#[test]
+fn game_screen_highlights_words_matching_the_input() {
+    let terminal = play_and_pause("al");
+    let cells = &terminal.backend().buffer().content;
+
+    let highlighted = cells
+        .iter()
+        .filter(|c| c.fg == Color::Cyan && c.modifier.contains(Modifier::BOLD))
+        .count();
+    assert_eq!(highlighted, 4);
+    // Only the word nearest the edge is marked as the one to be captured
+    let underlined = cells
+        .iter()
+        .filter(|c| c.modifier.contains(Modifier::UNDERLINED))
+        .count();
+    assert_eq!(underlined, 5);
+    assert!(!cells.iter().any(|c| c.fg == Color::Red));
+}
+
+#[test]
+fn game_screen_marks_mismatched_input() {
+    let mistyped = play_and_pause("ax");
+    let cells = &mistyped.backend().buffer().content;
+
+    assert!(cells.iter().any(|c| c.symbol == "x" && c.fg == Color::Red));
+    assert!(cells.iter().any(|c| c.symbol == "─" && c.fg == Color::Red));
+    assert!(!cells.iter().any(|c| c.fg == Color::Cyan));
+
+    // Empty input is not a mistake, even before any word is on the screen
+    let mut terminal = terminal();
+    let mut events = ScriptedEvents::new()
+        .key(KeyCode::Esc)
+        .key(KeyCode::Up)
+        .key(KeyCode::Enter);
+    let mut game_state = game_state();
+    game_state.word_pool = vec!["alpha".to_string(); 200];
+    game_screen::show_view(&mut terminal, &mut events, &mut game_state).unwrap();
+    assert!(game_state.engine.words().is_empty());
+    let cells = &terminal.backend().buffer().content;
+    assert!(!cells.iter().any(|c| c.fg == Color::Red));
+}
+
+#[test]
+fn game_screen_waits_for_a_large_enough_terminal() {
+    let mut terminal = Terminal::new(TestBackend::new(80, 12)).unwrap();
+    let mut events = ScriptedEvents::new()
+        .wait(Duration::from_secs(5))
+        .key(KeyCode::Esc);
+    let mut game_state = game_state();
+    game_state.word_pool = vec!["alpha".to_string(); 200];
+
+    let screen = game_screen::show_view(&mut terminal, &mut events, &mut game_state).unwrap();
+
+    assert_eq!(screen, Screen::Home);
+    assert!(game_state.engine.words().is_empty());
+    assert!(screen_text(&terminal).contains("The terminal is too small to play."));
+
+    // Too narrow is too small as well
+    let mut terminal = Terminal::new(TestBackend::new(60, 47)).unwrap();
+    let mut events = ScriptedEvents::new()
+        .wait(Duration::from_secs(5))
+        .key(KeyCode::Esc);
+    game_screen::show_view(&mut terminal, &mut events, &mut game_state).unwrap();
+    assert!(game_state.engine.words().is_empty());
+    assert!(screen_text(&terminal).contains("columns wide"));
+}
+
+#[test]
+fn long_sentences_stay_in_their_lane() {
+    let sentence = format!("{}.", "word ".repeat(12).trim_end());
+    assert_eq!(sentence.len(), MAX_SENTENCE_WIDTH);
+    let width = game_screen::MIN_WIDTH as u16;
+    let mut terminal = Terminal::new(TestBackend::new(width, 47)).unwrap();
+    let mut events = ScriptedEvents::new();
+    let mut game_state = game_state();
+    game_state.mode = Mode::TimeAttack { seconds: 6 };
+    game_state.word_list = WordList::Sentences(language_pack::find("english").unwrap());
+    game_state.word_pool = vec![sentence.clone(); 200];
+
+    let screen = game_screen::show_view(&mut terminal, &mut events, &mut game_state).unwrap();
+
+    assert_eq!(screen, Screen::End);
+    assert!(game_state.engine.words().len() > 1);
+    // Every sentence is drawn whole on the row of its lane, with nothing pushed
+    // onto the rows below
+    let rows: Vec<String> = screen_text(&terminal)
+        .lines()
+        .map(|row| row.to_string())
+        .collect();
+    for word in game_state.engine.words() {
+        assert!(rows[word.y + 2].contains(&sentence));
+    }
+    let drawn = rows.iter().filter(|row| row.contains("word")).count();
+    assert_eq!(drawn, game_state.engine.words().len());
+}
+
+#[test]
+fn game_screen_freezes_while_paused() {
+    let mut terminal = terminal();
+    let mut events = ScriptedEvents::new()
+        .wait(Duration::from_secs(1))
+        .key(KeyCode::Esc)
+        .wait(Duration::from_secs(5))
+        .key(KeyCode::Down)
+        .key(KeyCode::Down)
+        .key(KeyCode::Down)
+        .key(KeyCode::Down)
+        .key(KeyCode::Enter);
+    let mut game_state = game_state();
+    game_state.word_pool = vec!["alpha".to_string(); 200];
+
+    let screen = game_screen::show_view(&mut terminal, &mut events, &mut game_state).unwrap();
+
+    assert_eq!(screen, Screen::Home);
+    assert!(game_state.engine.stats().elapsed() < Duration::from_secs(2));
+    let text = screen_text(&terminal);
+    assert!(text.contains("Paused"));
+    assert!(text.contains(">> Quit to menu"));
+}
+
+#[test]
+fn focus_loss_pauses_the_game() {
+    let mut terminal = terminal();
+    let mut events = ScriptedEvents::new()
+        .wait(Duration::from_secs(1))
+        .event(Event::FocusLost)
+        .wait(Duration::from_secs(5))
+        .key(KeyCode::Enter)
+        .wait(Duration::from_secs(1))
+        .event(Event::Key(KeyEvent::new(
+            KeyCode::Char('p'),
+            KeyModifiers::CONTROL,
+        )))
+        .key(KeyCode::Up)
+        .key(KeyCode::Enter);
+    let mut game_state = game_state();
+    game_state.word_pool = vec!["alpha".to_string(); 200];
+
+    let screen = game_screen::show_view(&mut terminal, &mut events, &mut game_state).unwrap();
+
+    assert_eq!(screen, Screen::Home);
+    let elapsed = game_state.engine.stats().elapsed();
+    assert!(elapsed > Duration::from_secs(2) && elapsed < Duration::from_secs(3));
+}
+
+#[test]
+fn end_screen_records_high_score() {
+    let mut terminal = terminal();
+    let mut events = ScriptedEvents::new().key(KeyCode::Down).key(KeyCode::Enter);
+    let mut game_state = game_state();
+    let mut high_scores = HighScores::default();
+
+    let screen = end_screen::show_view(
+        &mut terminal,
+        &mut events,
+        &mut game_state,
+        &mut high_scores,
+    )
+    .unwrap();
+
+    assert_eq!(screen, Screen::Home);
+    assert_eq!(
+        high_scores
+            .category(
+                &game_state.word_list.to_string(),
+                game_state.mode,
+                game_state.difficulty,
+                game_state.input_mode,
+                game_state.engine.matching(),
+                game_state.punctuate,
+            )
+            .len(),
+        1
+    );
+    let text = screen_text(&terminal);
+    assert!(text.contains("Game Over!"));
+    assert!(text.contains("New high score! You placed #1."));
+    assert!(text.contains("Longest streak: 0 words"));
+    assert!(text.contains("0.0 for speed and distance"));
+    assert!(text.contains("tester"));
+}
+
+#[test]
+fn end_screen_adds_key_stats_to_the_history() {
+    let mut terminal = terminal();
+    let mut events = ScriptedEvents::new()
+        .key(KeyCode::Down)
+        .key(KeyCode::Down)
+        .key(KeyCode::Enter);
+    let mut game_state = game_state();
+    game_state.word_pool = vec!["alpha".to_string(); 200];
+    let mut game_events = ScriptedEvents::new()
+        .wait(Duration::from_secs(1))
+        .text("alqha");
+    game_screen::show_view(&mut terminal, &mut game_events, &mut game_state).unwrap();
+
+    let screen = end_screen::show_view(
+        &mut terminal,
+        &mut events,
+        &mut game_state,
+        &mut HighScores::default(),
+    )
+    .unwrap();
+
+    assert_eq!(screen, Screen::Stats);
+    assert_eq!(game_state.key_history.key('p').unwrap().misses, 1);
+    let text = screen_text(&terminal);
+    assert!(text.contains("Keys this game:"));
+    assert!(text.contains("Most missed: p 100%"));
+}
+
+#[test]
+fn stats_screen_shows_the_history() {
+    let mut terminal = terminal();
+    let mut events = ScriptedEvents::new().key(KeyCode::Esc);
+    let mut key_stats = KeyStats::default();
+    key_stats.record_key('q', false);
+    key_stats.record_key('q', true);
+
+    let screen = stats_screen::show_view(&mut terminal, &mut events, &key_stats).unwrap();
+
+    assert_eq!(screen, Screen::Home);
+    let text = screen_text(&terminal);
+    assert!(text.contains("Key stats over all your games"));
+    assert!(text.contains("Most missed: q 50%"));
+}
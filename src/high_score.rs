@@ -0,0 +1,271 @@
+use std::cmp::Ordering;
+
+use chrono::{Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    difficulty::Difficulty,
+    matching::Matching,
+    mode::{InputMode, Mode},
+    storage::{self, SaveFile},
+    GameError, GameState,
+};
+
+pub(crate) const TABLE_SIZE: usize = 10;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct HighScore {
+    pub(crate) name: String,
+    pub(crate) score: f32,
+    pub(crate) wpm: f32,
+    pub(crate) date: NaiveDate,
+    pub(crate) language: String,
+    pub(crate) mode: Mode,
+    #[serde(default)]
+    pub(crate) difficulty: Difficulty,
+    #[serde(default)]
+    pub(crate) input_mode: InputMode,
+    #[serde(default)]
+    pub(crate) matching: Matching,
+    #[serde(default)]
+    pub(crate) punctuate: bool,
+    // Length of the game, which ranks word count games
+    #[serde(default)]
+    pub(crate) seconds: f32,
+    // Words typed, which entries saved before it was recorded lack
+    #[serde(default)]
+    pub(crate) words: Option<usize>,
+}
+
+impl HighScore {
+    pub(crate) fn from_game(game_state: &GameState) -> Self {
+        HighScore {
+            name: game_state.player_name.to_owned(),
+            score: game_state.engine.score(),
+            wpm: game_state.engine.stats().net_wpm(),
+            date: Local::now().date_naive(),
+            language: game_state.word_list.to_string(),
+            mode: game_state.mode,
+            difficulty: game_state.difficulty,
+            input_mode: game_state.input_mode,
+            matching: game_state.engine.matching(),
+            punctuate: game_state.punctuate,
+            seconds: game_state.engine.stats().elapsed().as_secs_f32(),
+            words: Some(game_state.engine.stats().words),
+        }
+    }
+
+    // Order entries from best to worst, by time for modes where speed is the goal.
+    // Games that were ended before reaching their goal rank below all others, by score.
+    fn compare(&self, other: &HighScore) -> Ordering {
+        if !self.mode.ranks_by_time() {
+            return other.score.total_cmp(&self.score);
+        }
+        match (self.finished(), other.finished()) {
+            (true, true) => self.seconds.total_cmp(&other.seconds),
+            (false, false) => other.score.total_cmp(&self.score),
+            (finished, other_finished) => other_finished.cmp(&finished),
+        }
+    }
+
+    // Whether all the words of a word count game were typed
+    fn finished(&self) -> bool {
+        match (self.mode, self.words) {
+            (Mode::WordCount { words }, Some(typed)) => typed >= words as usize,
+            _ => true,
+        }
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub(crate) struct HighScores {
+    entries: Vec<HighScore>,
+    #[serde(skip)]
+    pub(crate) file: SaveFile,
+}
+
+impl HighScores {
+    pub(crate) fn load() -> Self {
+        let (high_scores, file) =
+            SaveFile::load::<HighScores>(storage::data_path("high_scores.json"), "high scores");
+        HighScores {
+            file,
+            ..high_scores
+        }
+    }
+
+    pub(crate) fn save(&self) -> Result<(), GameError> {
+        self.file.save(self, "high scores")
+    }
+
+    // Add a new entry and return its rank within its language, mode, difficulty,
+    // input mode and matching options
+    pub(crate) fn add(&mut self, entry: HighScore) -> usize {
+        let rank = self
+            .category(
+                &entry.language,
+                entry.mode,
+                entry.difficulty,
+                entry.input_mode,
+                entry.matching,
+                entry.punctuate,
+            )
+            .iter()
+            .filter(|e| e.compare(&entry) != Ordering::Greater)
+            .count();
+        self.entries.push(entry);
+        rank
+    }
+
+    // All entries for a language, mode, difficulty, input mode and matching
+    // options, sorted from best to worst
+    pub(crate) fn category(
+        &self,
+        language: &str,
+        mode: Mode,
+        difficulty: Difficulty,
+        input_mode: InputMode,
+        matching: Matching,
+        punctuate: bool,
+    ) -> Vec<&HighScore> {
+        let mut entries: Vec<&HighScore> = self
+            .entries
+            .iter()
+            .filter(|e| {
+                e.language == language
+                    && e.mode == mode
+                    && e.difficulty == difficulty
+                    && e.input_mode == input_mode
+                    && e.matching == matching
+                    && e.punctuate == punctuate
+            })
+            .collect();
+        entries.sort_by(|a, b| a.compare(b));
+        entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(mode: Mode, score: f32, seconds: f32) -> HighScore {
+        HighScore {
+            name: "tester".to_string(),
+            score,
+            wpm: 0.0,
+            date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            language: "English".to_string(),
+            mode,
+            difficulty: Difficulty::Normal,
+            input_mode: InputMode::Words,
+            matching: Matching::default(),
+            punctuate: false,
+            seconds,
+            words: None,
+        }
+    }
+
+    fn category(high_scores: &HighScores, mode: Mode, input_mode: InputMode) -> Vec<&HighScore> {
+        high_scores.category(
+            "English",
+            mode,
+            Difficulty::Normal,
+            input_mode,
+            Matching::default(),
+            false,
+        )
+    }
+
+    #[test]
+    fn modes_have_their_own_categories() {
+        let mut high_scores = HighScores::default();
+        assert_eq!(high_scores.add(entry(Mode::Classic, 10.0, 30.0)), 0);
+        assert_eq!(high_scores.add(entry(Mode::Classic, 20.0, 30.0)), 0);
+        assert_eq!(high_scores.add(entry(Mode::Zen, 5.0, 30.0)), 0);
+        let classic = category(&high_scores, Mode::Classic, InputMode::Words);
+        assert_eq!(classic.len(), 2);
+        assert_eq!(classic[0].score, 20.0);
+        let lives = Mode::Lives { lives: 3, regen: 0 };
+        assert!(category(&high_scores, lives, InputMode::Words).is_empty());
+    }
+
+    #[test]
+    fn word_count_ranks_by_time() {
+        let mode = Mode::WordCount { words: 25 };
+        let mut high_scores = HighScores::default();
+        assert_eq!(high_scores.add(entry(mode, 50.0, 40.0)), 0);
+        assert_eq!(high_scores.add(entry(mode, 10.0, 30.0)), 0);
+        assert_eq!(high_scores.add(entry(mode, 90.0, 60.0)), 2);
+        assert_eq!(
+            category(&high_scores, mode, InputMode::Words)[0].seconds,
+            30.0
+        );
+
+        // A game ended early is slower than every finished one, however short
+        let unfinished = |score, words| HighScore {
+            words: Some(words),
+            ..entry(mode, score, 2.0)
+        };
+        assert_eq!(high_scores.add(unfinished(0.0, 0)), 3);
+        assert_eq!(high_scores.add(unfinished(5.0, 3)), 3);
+        assert_eq!(high_scores.add(unfinished(20.0, 25)), 0);
+    }
+
+    #[test]
+    fn input_modes_have_their_own_categories() {
+        let mut high_scores = HighScores::default();
+        high_scores.add(entry(Mode::Classic, 10.0, 30.0));
+        let lock_on = HighScore {
+            input_mode: InputMode::LockOn,
+            ..entry(Mode::Classic, 5.0, 30.0)
+        };
+        assert_eq!(high_scores.add(lock_on), 0);
+        let category = category(&high_scores, Mode::Classic, InputMode::LockOn);
+        assert_eq!(category.len(), 1);
+        assert_eq!(category[0].score, 5.0);
+    }
+
+    #[test]
+    fn matching_options_have_their_own_categories() {
+        let mut high_scores = HighScores::default();
+        high_scores.add(entry(Mode::Classic, 10.0, 30.0));
+        let strict = Matching {
+            jamo: false,
+            strict: true,
+        };
+        let punctuated = HighScore {
+            matching: strict,
+            punctuate: true,
+            ..entry(Mode::Classic, 5.0, 30.0)
+        };
+        assert_eq!(high_scores.add(punctuated), 0);
+        assert_eq!(
+            category(&high_scores, Mode::Classic, InputMode::Words).len(),
+            1
+        );
+        let category = |punctuate| {
+            high_scores.category(
+                "English",
+                Mode::Classic,
+                Difficulty::Normal,
+                InputMode::Words,
+                strict,
+                punctuate,
+            )
+        };
+        assert!(category(false).is_empty());
+        assert_eq!(category(true)[0].score, 5.0);
+    }
+
+    #[test]
+    fn entries_saved_without_matching_options_load_as_loose() {
+        let entry: HighScore = serde_json::from_str(
+            r#"{"name":"tester","score":1.0,"wpm":0.0,"date":"2024-01-01",
+                "language":"English","mode":"Classic"}"#,
+        )
+        .unwrap();
+        assert_eq!(entry.matching, Matching::default());
+        assert!(!entry.punctuate);
+    }
+}
@@ -0,0 +1,166 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    storage::{self, SaveFile},
+    GameError,
+};
+
+// Bigrams typed fewer times than this are too noisy to rank
+const MIN_BIGRAM_SAMPLES: u32 = 3;
+
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+pub(crate) struct KeyCount {
+    pub(crate) hits: u32,
+    pub(crate) misses: u32,
+}
+
+impl KeyCount {
+    pub(crate) fn miss_rate(&self) -> f32 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            return 0.0;
+        }
+        self.misses as f32 / total as f32
+    }
+}
+
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+struct BigramTime {
+    total_ms: u64,
+    count: u32,
+}
+
+// Hits and misses per expected key, and how long each pair of keys takes to type
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub(crate) struct KeyStats {
+    keys: BTreeMap<char, KeyCount>,
+    bigrams: BTreeMap<String, BigramTime>,
+    #[serde(skip)]
+    pub(crate) file: SaveFile,
+}
+
+impl KeyStats {
+    pub(crate) fn load() -> Self {
+        let (key_stats, file) =
+            SaveFile::load::<KeyStats>(storage::data_path("key_stats.json"), "key stats");
+        KeyStats { file, ..key_stats }
+    }
+
+    pub(crate) fn save(&self) -> Result<(), GameError> {
+        self.file.save(self, "key stats")
+    }
+
+    pub(crate) fn record_key(&mut self, expected: char, correct: bool) {
+        let count = self.keys.entry(expected).or_default();
+        if correct {
+            count.hits += 1;
+        } else {
+            count.misses += 1;
+        }
+    }
+
+    pub(crate) fn record_bigram(&mut self, first: char, second: char, time: Duration) {
+        let bigram = self
+            .bigrams
+            .entry(format!("{}{}", first, second))
+            .or_default();
+        bigram.total_ms += time.as_millis() as u64;
+        bigram.count += 1;
+    }
+
+    // Add the stats of another game to these
+    pub(crate) fn merge(&mut self, other: &KeyStats) {
+        for (key, count) in other.keys.iter() {
+            let total = self.keys.entry(*key).or_default();
+            total.hits += count.hits;
+            total.misses += count.misses;
+        }
+        for (bigram, time) in other.bigrams.iter() {
+            let total = self.bigrams.entry(bigram.to_owned()).or_default();
+            total.total_ms += time.total_ms;
+            total.count += time.count;
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub(crate) fn key(&self, key: char) -> Option<KeyCount> {
+        self.keys.get(&key).copied()
+    }
+
+    // Keys with at least one miss, from the worst miss rate to the best
+    pub(crate) fn most_missed(&self, count: usize) -> Vec<(char, f32)> {
+        let mut keys: Vec<(char, f32)> = self
+            .keys
+            .iter()
+            .filter(|(_, c)| c.misses > 0)
+            .map(|(key, c)| (*key, c.miss_rate()))
+            .collect();
+        keys.sort_by(|a, b| b.1.total_cmp(&a.1));
+        keys.truncate(count);
+        keys
+    }
+
+    // Bigrams with the longest average time between their keys
+    pub(crate) fn slowest_bigrams(&self, count: usize) -> Vec<(String, Duration)> {
+        let mut bigrams: Vec<(String, Duration)> = self
+            .bigrams
+            .iter()
+            .filter(|(_, t)| t.count >= MIN_BIGRAM_SAMPLES)
+            .map(|(bigram, t)| {
+                (
+                    bigram.to_owned(),
+                    Duration::from_millis(t.total_ms / t.count as u64),
+                )
+            })
+            .collect();
+        bigrams.sort_by_key(|b| std::cmp::Reverse(b.1));
+        bigrams.truncate(count);
+        bigrams
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranks_keys_by_miss_rate() {
+        let mut stats = KeyStats::default();
+        stats.record_key('a', true);
+        stats.record_key('a', false);
+        stats.record_key('q', false);
+        stats.record_key('e', true);
+        assert_eq!(stats.most_missed(5), vec![('q', 1.0), ('a', 0.5)]);
+        assert_eq!(stats.key('e').unwrap().miss_rate(), 0.0);
+    }
+
+    #[test]
+    fn ranks_bigrams_with_enough_samples() {
+        let mut stats = KeyStats::default();
+        for _ in 0..3 {
+            stats.record_bigram('t', 'h', Duration::from_millis(100));
+            stats.record_bigram('q', 'u', Duration::from_millis(300));
+        }
+        stats.record_bigram('z', 'x', Duration::from_millis(900));
+        let slowest = stats.slowest_bigrams(5);
+        assert_eq!(slowest.len(), 2);
+        assert_eq!(slowest[0], ("qu".to_string(), Duration::from_millis(300)));
+    }
+
+    #[test]
+    fn merges_games_into_the_history() {
+        let mut history = KeyStats::default();
+        let mut game = KeyStats::default();
+        game.record_key('a', false);
+        game.record_bigram('a', 'b', Duration::from_millis(200));
+        history.merge(&game);
+        history.merge(&game);
+        assert_eq!(history.key('a').unwrap().misses, 2);
+        assert_eq!(history.bigrams["ab"].count, 2);
+    }
+}
@@ -0,0 +1,299 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::{storage, Asset, GameError};
+
+const BUILT_IN_DIR: &str = "languages";
+const MANIFEST_FILE: &str = "pack.toml";
+const WORDS_FILE: &str = "words.txt";
+const SENTENCES_FILE: &str = "sentences.txt";
+
+// Which way the words of a language read, and so which way they move
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum TextDirection {
+    #[default]
+    Ltr,
+    Rtl,
+}
+
+// The Unicode form words are put in when they are loaded
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Normalization {
+    #[default]
+    Nfc,
+    // Also folds compatibility characters, like full-width letters
+    Nfkc,
+}
+
+impl Normalization {
+    pub(crate) fn apply(&self, word: &str) -> String {
+        match self {
+            Normalization::Nfc => word.nfc().collect(),
+            Normalization::Nfkc => word.nfkc().collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub(crate) struct Manifest {
+    pub(crate) name: String,
+    pub(crate) script: String,
+    pub(crate) direction: TextDirection,
+    pub(crate) normalization: Normalization,
+    // The keyboard layout or input method the words are meant to be typed with
+    pub(crate) keyboard: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Source {
+    BuiltIn,
+    Dir(PathBuf),
+}
+
+// A directory with a manifest and a word list, either built in or in the config directory
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct LanguagePack {
+    // Name of the pack's directory, which also works on the command line
+    pub(crate) id: String,
+    pub(crate) manifest: Manifest,
+    source: Source,
+    // Why the manifest could not be read, reported when the pack is picked
+    error: Option<String>,
+}
+
+impl LanguagePack {
+    fn built_in(id: &str) -> Self {
+        let file_name = format!("{}/{}/{}", BUILT_IN_DIR, id, MANIFEST_FILE);
+        let manifest = read_asset(&file_name).and_then(|data| parse_manifest(&data, &file_name));
+        LanguagePack::new(id.to_string(), manifest, Source::BuiltIn)
+    }
+
+    fn from_dir(dir: &Path) -> Self {
+        let id = dir
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| dir.display().to_string());
+        let path = dir.join(MANIFEST_FILE);
+        let manifest = fs::read_to_string(&path)
+            .map_err(|err| {
+                GameError(format!(
+                    "Could not read language pack '{}': {}",
+                    path.display(),
+                    err
+                ))
+            })
+            .and_then(|data| parse_manifest(&data, &path.display().to_string()));
+        LanguagePack::new(id, manifest, Source::Dir(dir.to_path_buf()))
+    }
+
+    fn new(id: String, manifest: Result<Manifest, GameError>, source: Source) -> Self {
+        let (mut manifest, error) = match manifest {
+            Ok(manifest) => (manifest, None),
+            Err(err) => (Manifest::default(), Some(err.to_string())),
+        };
+        if manifest.name.is_empty() {
+            manifest.name = id.clone();
+        }
+        LanguagePack {
+            id,
+            manifest,
+            source,
+            error,
+        }
+    }
+
+    // The word list as it is stored, one word per line
+    pub(crate) fn read_words(&self) -> Result<String, GameError> {
+        self.read(WORDS_FILE)
+    }
+
+    // The sentences and quotes as they are stored, one per line
+    pub(crate) fn read_sentences(&self) -> Result<String, GameError> {
+        self.read(SENTENCES_FILE)
+    }
+
+    // Whether the pack comes with sentences as well as words
+    pub(crate) fn has_sentences(&self) -> bool {
+        match &self.source {
+            Source::BuiltIn => Asset::get(&self.asset_name(SENTENCES_FILE)).is_some(),
+            Source::Dir(dir) => dir.join(SENTENCES_FILE).is_file(),
+        }
+    }
+
+    fn read(&self, file: &str) -> Result<String, GameError> {
+        if let Some(error) = &self.error {
+            return Err(GameError(error.clone()));
+        }
+        match &self.source {
+            Source::BuiltIn => read_asset(&self.asset_name(file)),
+            Source::Dir(dir) => {
+                let path = dir.join(file);
+                fs::read_to_string(&path).map_err(|err| {
+                    GameError(format!(
+                        "Could not read word list '{}': {}",
+                        path.display(),
+                        err
+                    ))
+                })
+            }
+        }
+    }
+
+    fn asset_name(&self, file: &str) -> String {
+        format!("{}/{}/{}", BUILT_IN_DIR, self.id, file)
+    }
+
+    // One line about the pack for the home screen
+    pub(crate) fn describe(&self) -> String {
+        let mut parts = vec![];
+        if !self.manifest.script.is_empty() {
+            parts.push(format!("{} script", self.manifest.script));
+        }
+        if self.manifest.direction == TextDirection::Rtl {
+            parts.push("right to left".to_string());
+        }
+        if !self.manifest.keyboard.is_empty() {
+            parts.push(format!("keyboard: {}", self.manifest.keyboard));
+        }
+        if let Source::Dir(dir) = &self.source {
+            parts.push(format!("from {}", dir.display()));
+        }
+        format!("{}: {}", self.manifest.name, parts.join(", "))
+    }
+}
+
+fn read_asset(file_name: &str) -> Result<String, GameError> {
+    let asset = Asset::get(file_name)
+        .ok_or_else(|| GameError(format!("Built-in file '{}' is missing", file_name)))?;
+    String::from_utf8(asset.data.to_vec())
+        .map_err(|_| GameError(format!("Built-in file '{}' is not valid UTF-8", file_name)))
+}
+
+fn parse_manifest(data: &str, source: &str) -> Result<Manifest, GameError> {
+    toml::from_str(data)
+        .map_err(|err| GameError(format!("Language pack '{}' is invalid: {}", source, err)))
+}
+
+pub(crate) fn config_dir() -> Option<PathBuf> {
+    storage::config_path("languages")
+}
+
+// The built-in packs and the packs in the config directory, sorted by name.
+// A pack in the config directory replaces the built-in pack with the same id.
+pub(crate) fn discover() -> Vec<LanguagePack> {
+    let mut packs: Vec<LanguagePack> = Asset::iter()
+        .filter_map(|file| {
+            let id = file
+                .strip_prefix(BUILT_IN_DIR)?
+                .strip_prefix('/')?
+                .strip_suffix(MANIFEST_FILE)?
+                .strip_suffix('/')?;
+            Some(LanguagePack::built_in(id))
+        })
+        .collect();
+
+    let mut dirs: Vec<PathBuf> = config_dir()
+        .and_then(|dir| fs::read_dir(dir).ok())
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.is_dir())
+                .collect()
+        })
+        .unwrap_or_default();
+    dirs.sort();
+    for dir in dirs {
+        let pack = LanguagePack::from_dir(&dir);
+        match packs.iter_mut().find(|p| p.id == pack.id) {
+            Some(built_in) => *built_in = pack,
+            None => packs.push(pack),
+        }
+    }
+
+    packs.sort_by(|a, b| a.manifest.name.cmp(&b.manifest.name));
+    packs
+}
+
+// Look a pack up by its id or name, for the command line
+pub(crate) fn find(name: &str) -> Result<LanguagePack, String> {
+    let packs = discover();
+    packs
+        .iter()
+        .find(|p| p.id.eq_ignore_ascii_case(name) || p.manifest.name.eq_ignore_ascii_case(name))
+        .cloned()
+        .ok_or_else(|| {
+            let ids: Vec<&str> = packs.iter().map(|p| p.id.as_str()).collect();
+            format!(
+                "no language pack '{}', try one of: {}",
+                name,
+                ids.join(", ")
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_built_in_packs() {
+        let packs = discover();
+        for id in [
+            "afrikaans",
+            "english",
+            "german",
+            "japanese_romaji",
+            "korean",
+        ] {
+            assert!(packs.iter().any(|p| p.id == id), "{}", id);
+        }
+        let korean = find("Korean").unwrap();
+        assert_eq!(korean.manifest.script, "Hangul");
+        assert!(korean.read_words().unwrap().contains("경험"));
+        assert!(!korean.has_sentences());
+        assert!(find("english").unwrap().has_sentences());
+        assert_eq!(find("Japanese (romaji)").unwrap().id, "japanese_romaji");
+        assert!(find("klingon").unwrap_err().contains("english"));
+    }
+
+    #[test]
+    fn reads_packs_from_a_directory() {
+        let dir = std::env::temp_dir().join(format!("type_defender_pack_{}", std::process::id()));
+        let pack_dir = dir.join("hebrew");
+        fs::create_dir_all(&pack_dir).unwrap();
+        fs::write(
+            pack_dir.join(MANIFEST_FILE),
+            "name = \"Hebrew\"\nscript = \"Hebrew\"\ndirection = \"rtl\"\n",
+        )
+        .unwrap();
+        fs::write(pack_dir.join(WORDS_FILE), "שלום\n").unwrap();
+        let broken_dir = dir.join("broken");
+        fs::create_dir_all(&broken_dir).unwrap();
+        fs::write(broken_dir.join(MANIFEST_FILE), "direction = \"up\"\n").unwrap();
+
+        let pack = LanguagePack::from_dir(&pack_dir);
+        let broken = LanguagePack::from_dir(&broken_dir);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(pack.manifest.direction, TextDirection::Rtl);
+        assert_eq!(pack.manifest.normalization, Normalization::Nfc);
+        assert!(pack
+            .describe()
+            .starts_with("Hebrew: Hebrew script, right to left"));
+        assert_eq!(broken.manifest.name, "broken");
+        assert!(broken
+            .read_words()
+            .unwrap_err()
+            .to_string()
+            .contains("is invalid"));
+    }
+}
@@ -1,21 +1,47 @@
+mod adaptive;
+mod cli;
+mod config;
+mod difficulty;
+mod engine;
 mod game;
+mod high_score;
+mod key_stats;
+mod language_pack;
+mod matching;
+mod mode;
+mod practice;
+mod scoring;
+mod stats;
+mod storage;
+mod word_list;
 
-use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
+use clap::Parser;
+use cli::Args;
+use config::Config;
+use crossterm::event::{
+    DisableFocusChange, DisableMouseCapture, EnableFocusChange, EnableMouseCapture,
+};
 use crossterm::execute;
 use crossterm::terminal::{
     disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
 };
+use difficulty::{Difficulty, DifficultyCurve};
+use engine::{Engine, MIN_LANES};
+use game::{events::CrosstermEvents, Screen};
+use high_score::HighScores;
+use key_stats::KeyStats;
+use matching::Matching;
+use mode::{InputMode, Mode};
+use practice::Focus;
 use rust_embed::RustEmbed;
 use std::error::Error;
 use std::io::stdout;
 use std::time::Duration;
 use std::{fmt, io};
-use strum::Display;
-use strum_macros::EnumIter;
 use tui::backend::CrosstermBackend;
-use tui::text::Spans;
 use tui::widgets::ListState;
 use tui::Terminal;
+use word_list::WordList;
 
 const FPS: i32 = 60;
 const FRAME_TIME: Duration = Duration::from_micros(1_000_000 / FPS as u64);
@@ -24,83 +50,105 @@ const FRAME_TIME: Duration = Duration::from_micros(1_000_000 / FPS as u64);
 #[folder = "resources/"]
 struct Asset;
 
-#[derive(Clone, Display, EnumIter)]
-enum Language {
-    Afrikaans,
-    English,
-    Korean,
-}
-
-struct GameState<'a> {
-    language: Language,
-    score: f32,
-    wpm: f32,
+struct GameState {
+    word_list: WordList,
+    mode: Mode,
+    difficulty: Difficulty,
+    input_mode: InputMode,
+    matching: Matching,
+    // Give words random capitals and punctuation
+    punctuate: bool,
+    config: Config,
+    // Key stats over all games, to practise the weakest keys
+    key_history: KeyStats,
+    player_name: String,
+    fixed_seed: Option<u64>,
+    seed: u64,
+    pause_on_focus_loss: bool,
     word_pool: Vec<String>,
-    words: Vec<Word>,
-    word_slots: [i32; 40],
-    display_rows: Vec<Spans<'a>>,
+    engine: Engine,
 }
 
-impl GameState<'_> {
-    fn new() -> Self {
+impl GameState {
+    fn new(args: &Args) -> Self {
+        let seed = args.seed.unwrap_or_else(rand::random);
         GameState {
-            language: Language::English,
-            score: 0.0,
-            wpm: 20.0,
+            word_list: args
+                .words
+                .first()
+                .map(|path| WordList::from_path(path, args.sentences))
+                .unwrap_or_else(|| match args.sentences {
+                    true => WordList::Sentences(args.language.clone()),
+                    false => WordList::Pack(args.language.clone()),
+                }),
+            mode: args.mode(),
+            difficulty: args.difficulty,
+            input_mode: args.input,
+            matching: Matching {
+                jamo: args.jamo,
+                strict: args.strict || args.punctuate,
+            },
+            punctuate: args.punctuate,
+            config: Config::default(),
+            key_history: KeyStats::default(),
+            player_name: args.name.to_owned(),
+            fixed_seed: args.seed,
+            seed,
+            pause_on_focus_loss: true,
             word_pool: vec![],
-            words: vec![],
-            word_slots: [0; 40],
-            display_rows: vec![],
+            engine: Engine::new(
+                vec![],
+                seed,
+                MIN_LANES,
+                args.mode(),
+                args.difficulty.curve(None),
+            ),
         }
     }
-}
-
-#[derive(Debug)]
-struct GameError<'a>(&'a str);
-
-impl fmt::Display for GameError<'_> {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.0)
-    }
-}
-
-impl std::error::Error for GameError<'_> {}
-
-#[derive(Debug, Clone)]
-struct Word {
-    text: String,
-    found: bool,
-    x: f32,
-    y: usize,
-    speed: f32,
-}
 
-impl Word {
-    fn new(text: String, y: usize, speed: f32) -> Self {
-        Word {
-            text,
-            found: false,
-            x: 0.0,
-            y,
-            speed,
+    // Sentences are typed as they are written
+    fn matching_for(&self, word_list: &WordList) -> Matching {
+        Matching {
+            strict: self.matching.strict || word_list.is_sentences(),
+            ..self.matching
         }
     }
 
-    fn increment(&mut self) {
-        self.x += self.speed;
+    // Start a fresh game with the chosen words, only reusing the seed if it was fixed
+    fn new_game(&mut self, lanes: usize) {
+        self.seed = self.fixed_seed.unwrap_or_else(rand::random);
+        self.engine = Engine::new(
+            self.word_pool.clone(),
+            self.seed,
+            lanes,
+            self.mode,
+            self.difficulty_curve(),
+        );
+        self.engine.set_matching(self.matching_for(&self.word_list));
+        self.engine.set_punctuate(self.punctuate);
+        if self.mode == Mode::Practice {
+            self.engine
+                .set_focus(Focus::from_history(&self.key_history));
+        }
     }
 
-    fn progress(self) -> f32 {
-        self.x / 100.0
+    fn difficulty_curve(&self) -> DifficultyCurve {
+        self.difficulty
+            .curve(self.config.custom_difficulty.as_ref())
     }
 }
 
-impl PartialEq for Word {
-    fn eq(&self, other: &Self) -> bool {
-        self.text == other.text
+#[derive(Debug)]
+struct GameError(String);
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
     }
 }
 
+impl std::error::Error for GameError {}
+
 struct StatefulList<T> {
     state: ListState,
     items: Vec<T>,
@@ -144,45 +192,73 @@ impl<T> StatefulList<T> {
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
-    let result = std::panic::catch_unwind(|| {
-        let result = run_game();
-        match result {
-            Ok(_) => (),
-            Err(error) => println!("Error: {}", error),
-        }
-    });
+    let args = Args::parse();
+    let result = std::panic::catch_unwind(|| run_game(&args));
+
+    // restore terminal before reporting errors, as leaving the alternate screen clears them
+    disable_raw_mode()?;
+    execute!(
+        stdout(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        DisableFocusChange
+    )?;
     match result {
-        Ok(_) => (),
+        Ok(Ok(_)) => (),
+        Ok(Err(error)) => println!("Error: {}", error),
         Err(err) => println!("Error: {:?}", err.downcast_ref::<&str>()),
     }
-
-    // restore terminal
-    disable_raw_mode()?;
-    execute!(stdout(), LeaveAlternateScreen, DisableMouseCapture)?;
     Ok(())
 }
 
-fn run_game() -> Result<(), Box<dyn Error>> {
+fn run_game(args: &Args) -> Result<(), Box<dyn Error>> {
     // setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(
+        stdout,
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableFocusChange
+    )?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
+    let word_lists = word_list::discover(&args.words, args.sentences);
+    let mut high_scores = HighScores::load();
+    let mut events = CrosstermEvents::new();
+
+    let mut game_state = GameState::new(args);
+    game_state.config = Config::load();
+    game_state.key_history = KeyStats::load();
+    let mut screen = Screen::Home;
+    if args.no_menu {
+        game_state.word_pool = game_state.word_list.load()?;
+        screen = Screen::Game;
+    }
 
     loop {
-        let mut game_state = GameState::new();
         terminal.clear()?;
-        if !game::home_screen::show_view(&mut terminal, &mut game_state)? {
-            return Ok(());
-        }
-        terminal.clear()?;
-        if !game::game_screen::show_view(&mut terminal, &mut game_state)? {
-            return Ok(());
-        }
-        terminal.clear()?;
-        if !game::end_screen::show_view(&mut terminal, &mut game_state)? {
-            return Ok(());
-        }
+        screen = match screen {
+            Screen::Home => game::home_screen::show_view(
+                &mut terminal,
+                &mut events,
+                &mut game_state,
+                &word_lists,
+                &high_scores,
+            )?,
+            Screen::Game => {
+                game::game_screen::show_view(&mut terminal, &mut events, &mut game_state)?
+            }
+            Screen::End => game::end_screen::show_view(
+                &mut terminal,
+                &mut events,
+                &mut game_state,
+                &mut high_scores,
+            )?,
+            Screen::Stats => {
+                game::stats_screen::show_view(&mut terminal, &mut events, &game_state.key_history)?
+            }
+            Screen::Quit => return Ok(()),
+        };
     }
 }
@@ -0,0 +1,196 @@
+use serde::{Deserialize, Serialize};
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+
+const HANGUL_START: u32 = 0xAC00;
+const HANGUL_END: u32 = 0xD7A3;
+// Conjoining jamo, which decomposed (NFD) Hangul is made of
+const LEADING_START: u32 = 0x1100;
+const VOWEL_START: u32 = 0x1161;
+const TRAILING_START: u32 = 0x11A7;
+
+// The keys of each part of a syllable on a 2-set keyboard, in Unicode order
+const INITIALS: [&str; 19] = [
+    "ㄱ", "ㄲ", "ㄴ", "ㄷ", "ㄸ", "ㄹ", "ㅁ", "ㅂ", "ㅃ", "ㅅ", "ㅆ", "ㅇ", "ㅈ", "ㅉ", "ㅊ", "ㅋ",
+    "ㅌ", "ㅍ", "ㅎ",
+];
+const VOWELS: [&str; 21] = [
+    "ㅏ", "ㅐ", "ㅑ", "ㅒ", "ㅓ", "ㅔ", "ㅕ", "ㅖ", "ㅗ", "ㅗㅏ", "ㅗㅐ", "ㅗㅣ", "ㅛ", "ㅜ",
+    "ㅜㅓ", "ㅜㅔ", "ㅜㅣ", "ㅠ", "ㅡ", "ㅡㅣ", "ㅣ",
+];
+const FINALS: [&str; 28] = [
+    "", "ㄱ", "ㄲ", "ㄱㅅ", "ㄴ", "ㄴㅈ", "ㄴㅎ", "ㄷ", "ㄹ", "ㄹㄱ", "ㄹㅁ", "ㄹㅂ", "ㄹㅅ",
+    "ㄹㅌ", "ㄹㅍ", "ㄹㅎ", "ㅁ", "ㅂ", "ㅂㅅ", "ㅅ", "ㅆ", "ㅇ", "ㅈ", "ㅊ", "ㅋ", "ㅌ", "ㅍ",
+    "ㅎ",
+];
+// Letters typed as two keys, when they come on their own
+const COMPOUND_LETTERS: [(char, &str); 18] = [
+    ('ㄳ', "ㄱㅅ"),
+    ('ㄵ', "ㄴㅈ"),
+    ('ㄶ', "ㄴㅎ"),
+    ('ㄺ', "ㄹㄱ"),
+    ('ㄻ', "ㄹㅁ"),
+    ('ㄼ', "ㄹㅂ"),
+    ('ㄽ', "ㄹㅅ"),
+    ('ㄾ', "ㄹㅌ"),
+    ('ㄿ', "ㄹㅍ"),
+    ('ㅀ', "ㄹㅎ"),
+    ('ㅄ', "ㅂㅅ"),
+    ('ㅘ', "ㅗㅏ"),
+    ('ㅙ', "ㅗㅐ"),
+    ('ㅚ', "ㅗㅣ"),
+    ('ㅝ', "ㅜㅓ"),
+    ('ㅞ', "ㅜㅔ"),
+    ('ㅟ', "ㅜㅣ"),
+    ('ㅢ', "ㅡㅣ"),
+];
+
+// How typed text is compared with the words on the screen
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Matching {
+    // Compare Hangul by the keys that type it, so that decomposed input and
+    // unfinished syllables match too
+    pub(crate) jamo: bool,
+    // Compare capitals too, for text typed with its case and punctuation
+    pub(crate) strict: bool,
+}
+
+impl Matching {
+    // The form of a text that is compared: composed (NFC), in lower case unless
+    // strict, and with Hangul split into keys when matching jamo
+    pub(crate) fn form(&self, text: &str) -> String {
+        let text = text.nfc().collect::<String>();
+        let text = if self.strict {
+            text
+        } else {
+            text.to_lowercase()
+        };
+        if self.jamo {
+            to_jamo(&text)
+        } else {
+            text
+        }
+    }
+
+    // Bytes at the start of the text that the input has typed, in whole
+    // graphemes, or zero if the input does not match the start of the text
+    pub(crate) fn typed_len(&self, text: &str, input: &str) -> usize {
+        let input = self.form(input);
+        if input.is_empty() || !self.form(text).starts_with(&input) {
+            return 0;
+        }
+        let mut typed = 0;
+        for (start, grapheme) in text.grapheme_indices(true) {
+            let end = start + grapheme.len();
+            if !input.starts_with(&self.form(&text[..end])) {
+                break;
+            }
+            typed = end;
+        }
+        typed
+    }
+}
+
+// Split Hangul into the keys of a 2-set keyboard, leaving other text as it is
+fn to_jamo(text: &str) -> String {
+    let mut jamo = String::new();
+    for c in text.chars() {
+        if let Some(keys) = syllable_keys(c) {
+            jamo.extend(keys);
+        } else if let Some(keys) = conjoining_keys(c as u32) {
+            jamo.push_str(keys);
+        } else if let Some((_, keys)) = COMPOUND_LETTERS.iter().find(|(letter, _)| *letter == c) {
+            jamo.push_str(keys);
+        } else {
+            jamo.push(c);
+        }
+    }
+    jamo
+}
+
+// The keys of the initial, vowel and final of a Hangul syllable
+pub(crate) fn syllable_keys(c: char) -> Option<[&'static str; 3]> {
+    let code = c as u32;
+    if !(HANGUL_START..=HANGUL_END).contains(&code) {
+        return None;
+    }
+    let index = (code - HANGUL_START) as usize;
+    Some([
+        INITIALS[index / (VOWELS.len() * FINALS.len())],
+        VOWELS[index % (VOWELS.len() * FINALS.len()) / FINALS.len()],
+        FINALS[index % FINALS.len()],
+    ])
+}
+
+// The keys of a conjoining jamo left over after composing
+fn conjoining_keys(code: u32) -> Option<&'static str> {
+    let index = |start: u32, len: usize| {
+        code.checked_sub(start)
+            .map(|i| i as usize)
+            .filter(|&i| i < len)
+    };
+    index(LEADING_START, INITIALS.len())
+        .map(|i| INITIALS[i])
+        .or_else(|| index(VOWEL_START, VOWELS.len()).map(|i| VOWELS[i]))
+        .or_else(|| {
+            index(TRAILING_START, FINALS.len())
+                .filter(|&i| i > 0)
+                .map(|i| FINALS[i])
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_composed_text() {
+        let matching = Matching::default();
+        // "é" typed as "e" and a combining accent, and "한" as conjoining jamo
+        assert_eq!(matching.form("Cafe\u{301}"), "café");
+        assert_eq!(matching.form("\u{1112}\u{1161}\u{11AB}"), "한");
+        assert_eq!(matching.form("한"), "한");
+    }
+
+    #[test]
+    fn strict_matching_keeps_capitals() {
+        let strict = Matching {
+            strict: true,
+            ..Matching::default()
+        };
+        assert_eq!(strict.form("Hello, World!"), "Hello, World!");
+        assert_eq!(strict.typed_len("Hello, World!", "Hello, w"), 0);
+        assert_eq!(strict.typed_len("Hello, World!", "Hello, W"), 8);
+    }
+
+    #[test]
+    fn splits_hangul_into_keys() {
+        let matching = Matching {
+            jamo: true,
+            ..Matching::default()
+        };
+        assert_eq!(matching.form("한글"), "ㅎㅏㄴㄱㅡㄹ");
+        assert_eq!(matching.form("꽉"), "ㄲㅗㅏㄱ");
+        assert_eq!(matching.form("닭"), "ㄷㅏㄹㄱ");
+        assert_eq!(matching.form("하ㄴ"), matching.form("한")[..9]);
+        assert_eq!(matching.form("ㅘ\u{1100}"), "ㅗㅏㄱ");
+        assert_eq!(matching.form("abc"), "abc");
+    }
+
+    #[test]
+    fn typed_length_covers_whole_graphemes() {
+        let matching = Matching {
+            jamo: true,
+            ..Matching::default()
+        };
+        assert_eq!(matching.typed_len("한글", "ㅎㅏ"), 0);
+        assert_eq!(matching.typed_len("한글", "한ㄱ"), "한".len());
+        assert_eq!(matching.typed_len("한글", "ㅎㅏㄴㄱㅡㄹ"), "한글".len());
+        assert_eq!(matching.typed_len("한글", "핫"), 0);
+        assert_eq!(
+            Matching::default().typed_len("café", "CAFE\u{301}"),
+            "café".len()
+        );
+        assert_eq!(Matching::default().typed_len("alpha", "alx"), 0);
+    }
+}
@@ -0,0 +1,85 @@
+use std::fmt;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use strum::Display;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) enum Mode {
+    // The game ends as soon as a word reaches the edge
+    Classic,
+    // Type as much as possible before the time runs out
+    TimeAttack {
+        seconds: u32,
+    },
+    // Type a number of words as fast as possible
+    WordCount {
+        words: u32,
+    },
+    // A number of words may reach the edge before the game ends. A streak of
+    // regen words restores a life, unless regen is zero.
+    Lives {
+        lives: u32,
+        #[serde(default)]
+        regen: u32,
+    },
+    // Words that reach the edge are simply removed
+    Zen,
+    // Like zen, but favours words with the keys missed most in earlier games
+    Practice,
+}
+
+impl Mode {
+    // The modes offered on the home screen
+    pub(crate) fn presets() -> Vec<Mode> {
+        vec![
+            Mode::Classic,
+            Mode::TimeAttack { seconds: 60 },
+            Mode::TimeAttack { seconds: 120 },
+            Mode::TimeAttack { seconds: 300 },
+            Mode::WordCount { words: 25 },
+            Mode::WordCount { words: 50 },
+            Mode::WordCount { words: 100 },
+            Mode::Lives { lives: 3, regen: 0 },
+            Mode::Lives { lives: 5, regen: 0 },
+            Mode::Lives {
+                lives: 3,
+                regen: 10,
+            },
+            Mode::Zen,
+            Mode::Practice,
+        ]
+    }
+
+    // Whether a lower time ranks higher than a higher score
+    pub(crate) fn ranks_by_time(&self) -> bool {
+        matches!(self, Mode::WordCount { .. })
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Mode::Classic => write!(f, "Classic"),
+            Mode::TimeAttack { seconds } => write!(f, "Time attack ({}s)", seconds),
+            Mode::WordCount { words } => write!(f, "Word count ({})", words),
+            Mode::Lives { lives, regen: 0 } => write!(f, "Lives ({})", lives),
+            Mode::Lives { lives, regen } => write!(f, "Lives ({}, +1/{})", lives, regen),
+            Mode::Zen => write!(f, "Zen"),
+            Mode::Practice => write!(f, "Practice"),
+        }
+    }
+}
+
+// How typed keys find the word they are meant for
+#[derive(Clone, Copy, Debug, Default, PartialEq, Display, ValueEnum, Serialize, Deserialize)]
+pub(crate) enum InputMode {
+    // Type a whole word into the input box, capturing the matching word nearest
+    // the edge
+    #[default]
+    Words,
+    // The first key locks on to the nearest word starting with it, and every
+    // key after that shoots the next letter off that word
+    #[strum(serialize = "Lock-on")]
+    LockOn,
+}
@@ -0,0 +1,100 @@
+use std::fmt;
+
+use crate::key_stats::KeyStats;
+
+const FOCUS_KEYS: usize = 3;
+const FOCUS_BIGRAMS: usize = 2;
+// Extra chance of picking a word for every focus key or bigram it contains
+const KEY_WEIGHT: f32 = 2.0;
+const BIGRAM_WEIGHT: f32 = 3.0;
+
+// The keys and bigrams a practice game favours
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Focus {
+    keys: Vec<char>,
+    bigrams: Vec<String>,
+}
+
+impl Focus {
+    // The most missed keys and the slowest bigrams from earlier games
+    pub(crate) fn from_history(history: &KeyStats) -> Self {
+        Focus {
+            keys: history
+                .most_missed(FOCUS_KEYS)
+                .into_iter()
+                .map(|(key, _)| key)
+                .collect(),
+            bigrams: history
+                .slowest_bigrams(FOCUS_BIGRAMS)
+                .into_iter()
+                .map(|(bigram, _)| bigram)
+                .collect(),
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.keys.is_empty() && self.bigrams.is_empty()
+    }
+
+    // How likely a word is to be picked, relative to a word without any focus keys
+    pub(crate) fn weight(&self, word: &str) -> f32 {
+        let word = word.to_lowercase();
+        let keys = word.chars().filter(|c| self.keys.contains(c)).count();
+        let bigrams: usize = self
+            .bigrams
+            .iter()
+            .map(|bigram| word.matches(bigram.as_str()).count())
+            .sum();
+        1.0 + KEY_WEIGHT * keys as f32 + BIGRAM_WEIGHT * bigrams as f32
+    }
+}
+
+impl fmt::Display for Focus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "all keys, until there are stats from earlier games");
+        }
+        let parts: Vec<String> = self
+            .keys
+            .iter()
+            .map(|key| key.to_string())
+            .chain(self.bigrams.iter().map(|bigram| format!("'{}'", bigram)))
+            .collect();
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn history() -> KeyStats {
+        let mut history = KeyStats::default();
+        history.record_key('q', false);
+        history.record_key('z', false);
+        history.record_key('z', true);
+        history.record_key('e', true);
+        for _ in 0..3 {
+            history.record_bigram('t', 'h', Duration::from_millis(400));
+        }
+        history
+    }
+
+    #[test]
+    fn focuses_on_the_weakest_keys() {
+        let focus = Focus::from_history(&history());
+        assert_eq!(focus.to_string(), "q, z, 'th'");
+        assert!(Focus::from_history(&KeyStats::default()).is_empty());
+    }
+
+    #[test]
+    fn weighs_words_by_focus_keys() {
+        let focus = Focus::from_history(&history());
+        assert_eq!(focus.weight("tea"), 1.0);
+        assert_eq!(focus.weight("quiz"), 5.0);
+        assert_eq!(focus.weight("the"), 4.0);
+        assert_eq!(Focus::default().weight("quiz"), 1.0);
+    }
+}
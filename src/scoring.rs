@@ -0,0 +1,165 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::matching;
+
+// Words of this many graphemes score the same as before length counted
+const AVERAGE_WORD_LENGTH: f32 = 5.0;
+// Letters that are rare in most word lists or far from the home row
+const HARD_LETTERS: &str = "jqxzkvbyw";
+const HARD_LETTER_WEIGHT: f32 = 0.5;
+// Extra weight for each jamo keystroke after the first in a Hangul syllable
+const JAMO_WEIGHT: f32 = 0.25;
+
+// Keys typed with shift on a 2-set keyboard
+const SHIFTED_KEYS: &str = "ㄲㄸㅃㅆㅉㅒㅖ";
+
+// How a captured word's score was made up
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct WordScore {
+    // Points for the word's speed and how far it still had to go
+    pub(crate) base: f32,
+    pub(crate) length: f32,
+    pub(crate) difficulty: f32,
+    pub(crate) multiplier: u32,
+}
+
+impl WordScore {
+    pub(crate) fn total(&self) -> f32 {
+        self.base * self.length * self.difficulty * self.multiplier as f32
+    }
+}
+
+// Points earned over a game, split by what earned them. The parts add up to the total.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct ScoreBreakdown {
+    pub(crate) speed: f32,
+    pub(crate) length: f32,
+    pub(crate) difficulty: f32,
+    pub(crate) streak: f32,
+}
+
+impl ScoreBreakdown {
+    pub(crate) fn add(&mut self, score: &WordScore) {
+        let with_length = score.base * score.length;
+        let with_difficulty = with_length * score.difficulty;
+        self.speed += score.base;
+        self.length += with_length - score.base;
+        self.difficulty += with_difficulty - with_length;
+        self.streak += score.total() - with_difficulty;
+    }
+
+    pub(crate) fn total(&self) -> f32 {
+        self.speed + self.length + self.difficulty + self.streak
+    }
+}
+
+// Faster words typed closer to the start are worth more, as are longer and
+// harder words. The base score was tuned for speeds per frame at 60 FPS, hence
+// the division.
+pub(crate) fn score_word(text: &str, progress: f32, speed: f32, multiplier: u32) -> WordScore {
+    WordScore {
+        base: 500.0 * (1.0 - progress).powf(3.0) * speed / 60.0,
+        length: grapheme_length(text) as f32 / AVERAGE_WORD_LENGTH,
+        difficulty: difficulty(text),
+        multiplier,
+    }
+}
+
+pub(crate) fn grapheme_length(text: &str) -> usize {
+    text.graphemes(true).count()
+}
+
+// One plus the average extra effort per grapheme
+pub(crate) fn difficulty(text: &str) -> f32 {
+    let length = grapheme_length(text);
+    if length == 0 {
+        return 1.0;
+    }
+    let effort: f32 = text.graphemes(true).map(grapheme_effort).sum();
+    1.0 + effort / length as f32
+}
+
+fn grapheme_effort(grapheme: &str) -> f32 {
+    let mut chars = grapheme.chars();
+    let Some(c) = chars.next() else {
+        return 0.0;
+    };
+    if let Some(keystrokes) = hangul_keystrokes(c) {
+        return (keystrokes - 1) as f32 * JAMO_WEIGHT;
+    }
+    let c = c.to_lowercase().next().unwrap_or(c);
+    if !c.is_ascii() || chars.next().is_some() {
+        // Accented letters need a dead key or a compose sequence
+        HARD_LETTER_WEIGHT
+    } else if HARD_LETTERS.contains(c) {
+        HARD_LETTER_WEIGHT
+    } else {
+        0.0
+    }
+}
+
+// Keys pressed to type a Hangul syllable on a 2-set keyboard, counting shift
+pub(crate) fn hangul_keystrokes(c: char) -> Option<u32> {
+    let keys = matching::syllable_keys(c)?.concat();
+    Some(
+        keys.chars()
+            .map(|key| if SHIFTED_KEYS.contains(key) { 2 } else { 1 })
+            .sum(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn length_counts_graphemes() {
+        assert_eq!(grapheme_length("the"), 3);
+        assert_eq!(grapheme_length("e\u{301}ke"), 3);
+        assert_eq!(grapheme_length("한국어"), 3);
+    }
+
+    #[test]
+    fn longer_words_score_more() {
+        let short = score_word("the", 0.0, 1.5, 1);
+        let long = score_word("programeerder", 0.0, 1.5, 1);
+        assert!(long.total() > short.total());
+    }
+
+    #[test]
+    fn earlier_capture_scores_more() {
+        assert!(
+            score_word("alpha", 0.1, 1.5, 1).total() > score_word("alpha", 0.6, 1.5, 1).total()
+        );
+    }
+
+    #[test]
+    fn rare_letters_are_harder() {
+        assert_eq!(difficulty("tea"), 1.0);
+        assert!(difficulty("quiz") > difficulty("tent"));
+        assert!(difficulty("sê") > difficulty("se"));
+    }
+
+    #[test]
+    fn hangul_counts_jamo_keystrokes() {
+        assert_eq!(hangul_keystrokes('가'), Some(2));
+        assert_eq!(hangul_keystrokes('한'), Some(3));
+        assert_eq!(hangul_keystrokes('꽉'), Some(5));
+        assert_eq!(hangul_keystrokes('닭'), Some(4));
+        assert_eq!(hangul_keystrokes('a'), None);
+        assert!(difficulty("닭") > difficulty("가"));
+    }
+
+    #[test]
+    fn breakdown_adds_up_to_the_total() {
+        let mut breakdown = ScoreBreakdown::default();
+        let first = score_word("quiz", 0.2, 2.0, 1);
+        let second = score_word("programeerder", 0.2, 2.0, 3);
+        breakdown.add(&first);
+        breakdown.add(&second);
+        let total = first.total() + second.total();
+        assert!((breakdown.total() - total).abs() < 0.001);
+        assert!(breakdown.streak > 0.0);
+        assert!(breakdown.length > 0.0);
+    }
+}
@@ -0,0 +1,121 @@
+use std::{collections::VecDeque, time::Duration};
+
+const ROLLING_WINDOW: Duration = Duration::from_secs(30);
+const CHARS_PER_WORD: f32 = 5.0;
+
+#[derive(Clone, Default)]
+pub(crate) struct TypingStats {
+    pub(crate) typed: usize,
+    pub(crate) correct: usize,
+    pub(crate) backspaces: usize,
+    pub(crate) words: usize,
+    elapsed: Duration,
+    recent: VecDeque<(Duration, bool)>,
+}
+
+impl TypingStats {
+    pub(crate) fn tick(&mut self, dt: Duration) {
+        self.elapsed += dt;
+        while let Some((time, _)) = self.recent.front() {
+            if self.elapsed - *time <= ROLLING_WINDOW {
+                break;
+            }
+            self.recent.pop_front();
+        }
+    }
+
+    pub(crate) fn record_key(&mut self, correct: bool) {
+        self.typed += 1;
+        if correct {
+            self.correct += 1;
+        }
+        self.recent.push_back((self.elapsed, correct));
+    }
+
+    pub(crate) fn record_backspace(&mut self) {
+        self.backspaces += 1;
+    }
+
+    pub(crate) fn record_word(&mut self) {
+        self.words += 1;
+    }
+
+    pub(crate) fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub(crate) fn accuracy(&self) -> f32 {
+        if self.typed == 0 {
+            return 100.0;
+        }
+        self.correct as f32 / self.typed as f32 * 100.0
+    }
+
+    pub(crate) fn gross_wpm(&self) -> f32 {
+        wpm(self.typed, self.elapsed)
+    }
+
+    // Gross speed less one word per minute for every mistake
+    pub(crate) fn net_wpm(&self) -> f32 {
+        net_wpm(self.typed, self.correct, self.elapsed)
+    }
+
+    pub(crate) fn rolling_gross_wpm(&self) -> f32 {
+        wpm(self.recent.len(), self.rolling_duration())
+    }
+
+    pub(crate) fn rolling_net_wpm(&self) -> f32 {
+        let correct = self.recent.iter().filter(|(_, correct)| *correct).count();
+        net_wpm(self.recent.len(), correct, self.rolling_duration())
+    }
+
+    fn rolling_duration(&self) -> Duration {
+        self.elapsed.min(ROLLING_WINDOW)
+    }
+}
+
+fn wpm(chars: usize, duration: Duration) -> f32 {
+    let minutes = duration.as_secs_f32() / 60.0;
+    if minutes <= 0.0 {
+        return 0.0;
+    }
+    chars as f32 / CHARS_PER_WORD / minutes
+}
+
+fn net_wpm(typed: usize, correct: usize, duration: Duration) -> f32 {
+    let minutes = duration.as_secs_f32() / 60.0;
+    if minutes <= 0.0 {
+        return 0.0;
+    }
+    let errors = (typed - correct) as f32;
+    (wpm(typed, duration) - errors / minutes).max(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn speed_and_accuracy_over_the_whole_game() {
+        let mut stats = TypingStats::default();
+        for i in 0..60 {
+            stats.record_key(i % 10 != 0);
+        }
+        stats.tick(Duration::from_secs(60));
+
+        assert_eq!(stats.gross_wpm(), 12.0);
+        assert_eq!(stats.net_wpm(), 6.0);
+        assert_eq!(stats.accuracy(), 90.0);
+    }
+
+    #[test]
+    fn rolling_speed_forgets_old_keys() {
+        let mut stats = TypingStats::default();
+        for _ in 0..50 {
+            stats.record_key(true);
+        }
+        stats.tick(ROLLING_WINDOW + Duration::from_secs(1));
+        assert_eq!(stats.rolling_gross_wpm(), 0.0);
+        assert_eq!(stats.gross_wpm(), 50.0 / CHARS_PER_WORD / (31.0 / 60.0));
+    }
+}
@@ -0,0 +1,155 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::GameError;
+
+// Where a file with saved state goes in the data directory
+pub(crate) fn data_path(file_name: &str) -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("type_defender").join(file_name))
+}
+
+// Where a file or directory of settings goes in the config directory. Tests
+// look in an empty directory instead, so that they do not depend on the
+// player's word lists and language packs.
+pub(crate) fn config_path(name: &str) -> Option<PathBuf> {
+    let dir = if cfg!(test) {
+        Some(std::env::temp_dir().join("type_defender_empty_config"))
+    } else {
+        dirs::config_dir().map(|dir| dir.join("type_defender"))
+    };
+    dir.map(|dir| dir.join(name))
+}
+
+// Read what was saved, or the default if nothing was saved yet. What the file
+// holds, like "high scores", is named in errors. Callers that get an error
+// should not save over the file, so that it can still be fixed by hand.
+pub(crate) fn load_json<T: DeserializeOwned + Default>(
+    path: &Path,
+    what: &str,
+) -> Result<T, GameError> {
+    if !path.exists() {
+        return Ok(T::default());
+    }
+    let data = fs::read_to_string(path).map_err(|err| {
+        GameError(format!(
+            "Could not read {} from '{}': {}",
+            what,
+            path.display(),
+            err
+        ))
+    })?;
+    serde_json::from_str(&data).map_err(|err| {
+        GameError(format!(
+            "The {} in '{}' are corrupt: {}",
+            what,
+            path.display(),
+            err
+        ))
+    })
+}
+
+// Where a saved value lives, and why it could not be loaded. Values keep one of
+// these next to their data and skip it when serialized.
+#[derive(Clone, Default)]
+pub(crate) struct SaveFile {
+    // None only keeps the value in memory
+    path: Option<PathBuf>,
+    pub(crate) error: Option<String>,
+}
+
+impl SaveFile {
+    // Load the value at the path, or the default without a path if the file is
+    // broken, so that it is not saved over
+    pub(crate) fn load<T: DeserializeOwned + Default>(
+        path: Option<PathBuf>,
+        what: &str,
+    ) -> (T, SaveFile) {
+        let Some(path) = path else {
+            return (T::default(), SaveFile::default());
+        };
+        match load_json(&path, what) {
+            Ok(value) => (
+                value,
+                SaveFile {
+                    path: Some(path),
+                    error: None,
+                },
+            ),
+            Err(err) => (
+                T::default(),
+                SaveFile {
+                    path: None,
+                    error: Some(err.to_string()),
+                },
+            ),
+        }
+    }
+
+    pub(crate) fn save<T: Serialize>(&self, value: &T, what: &str) -> Result<(), GameError> {
+        match &self.path {
+            Some(path) => save_json(path, value, what),
+            None => Ok(()),
+        }
+    }
+}
+
+pub(crate) fn save_json<T: Serialize>(path: &Path, value: &T, what: &str) -> Result<(), GameError> {
+    let to_error = |err: &dyn std::error::Error| {
+        GameError(format!(
+            "Could not save {} to '{}': {}",
+            what,
+            path.display(),
+            err
+        ))
+    };
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|err| to_error(&err))?;
+    }
+    let data = serde_json::to_string_pretty(value).map_err(|err| to_error(&err))?;
+    fs::write(path, data).map_err(|err| to_error(&err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saves_and_loads_json() {
+        let dir =
+            std::env::temp_dir().join(format!("type_defender_storage_{}", std::process::id()));
+        let path = dir.join("numbers.json");
+
+        let missing: Vec<u32> = load_json(&path, "numbers").unwrap();
+        save_json(&path, &vec![1, 2, 3], "numbers").unwrap();
+        let loaded: Vec<u32> = load_json(&path, "numbers").unwrap();
+        fs::write(&path, "[1,").unwrap();
+        let corrupt = load_json::<Vec<u32>>(&path, "numbers").unwrap_err();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(missing.is_empty());
+        assert_eq!(loaded, vec![1, 2, 3]);
+        assert!(corrupt.to_string().starts_with("The numbers in"));
+    }
+
+    #[test]
+    fn broken_files_are_not_saved_over() {
+        let dir =
+            std::env::temp_dir().join(format!("type_defender_save_file_{}", std::process::id()));
+        let path = dir.join("numbers.json");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&path, "[1,").unwrap();
+
+        let (numbers, file) = SaveFile::load::<Vec<u32>>(Some(path.clone()), "numbers");
+        file.save(&vec![1, 2, 3], "numbers").unwrap();
+        let kept = fs::read_to_string(&path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(numbers.is_empty());
+        assert!(file.error.unwrap().starts_with("The numbers in"));
+        assert_eq!(kept, "[1,");
+    }
+}
@@ -0,0 +1,258 @@
+use std::{
+    collections::HashSet,
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    language_pack::{self, LanguagePack, Normalization, TextDirection},
+    storage, GameError,
+};
+
+// Longer sentences would not fit on the screen next to the border
+pub(crate) const MAX_SENTENCE_WIDTH: usize = 60;
+
+#[derive(Clone, PartialEq)]
+pub(crate) enum WordList {
+    Pack(LanguagePack),
+    // The sentences and quotes that come with a language pack
+    Sentences(LanguagePack),
+    Custom {
+        name: String,
+        path: PathBuf,
+        // One sentence per line instead of one word
+        sentences: bool,
+    },
+}
+
+impl WordList {
+    pub(crate) fn from_path(path: &Path, sentences: bool) -> Self {
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.display().to_string());
+        WordList::Custom {
+            name,
+            path: path.to_path_buf(),
+            sentences,
+        }
+    }
+
+    pub(crate) fn load(&self) -> Result<Vec<String>, GameError> {
+        let data = match self {
+            WordList::Pack(pack) => pack.read_words()?,
+            WordList::Sentences(pack) if !pack.has_sentences() => {
+                return Err(GameError(format!(
+                    "Language pack '{}' has no sentences",
+                    pack.manifest.name
+                )))
+            }
+            WordList::Sentences(pack) => pack.read_sentences()?,
+            WordList::Custom { path, .. } => fs::read_to_string(path).map_err(|err| {
+                GameError(format!(
+                    "Could not read word list '{}': {}",
+                    path.display(),
+                    err
+                ))
+            })?,
+        };
+
+        let words = if self.is_sentences() {
+            parse_sentences(&data, self.normalization())
+        } else {
+            parse(&data, self.normalization())
+        };
+        if words.is_empty() {
+            let source = match self {
+                WordList::Pack(pack) | WordList::Sentences(pack) => pack.manifest.name.to_owned(),
+                WordList::Custom { path, .. } => path.display().to_string(),
+            };
+            return Err(GameError(match self.is_sentences() {
+                true => format!(
+                    "Word list '{}' has no sentences of up to {} columns in it",
+                    source, MAX_SENTENCE_WIDTH
+                ),
+                false => format!("Word list '{}' has no words in it", source),
+            }));
+        }
+        Ok(words)
+    }
+
+    // Sentences are typed with their capitals and punctuation
+    pub(crate) fn is_sentences(&self) -> bool {
+        match self {
+            WordList::Pack(_) => false,
+            WordList::Sentences(_) => true,
+            WordList::Custom { sentences, .. } => *sentences,
+        }
+    }
+
+    pub(crate) fn direction(&self) -> TextDirection {
+        match self {
+            WordList::Pack(pack) | WordList::Sentences(pack) => pack.manifest.direction,
+            WordList::Custom { .. } => TextDirection::default(),
+        }
+    }
+
+    fn normalization(&self) -> Normalization {
+        match self {
+            WordList::Pack(pack) | WordList::Sentences(pack) => pack.manifest.normalization,
+            WordList::Custom { .. } => Normalization::default(),
+        }
+    }
+
+    // One line about the list for the home screen
+    pub(crate) fn describe(&self) -> String {
+        let description = match self {
+            WordList::Pack(pack) | WordList::Sentences(pack) => pack.describe(),
+            WordList::Custom { name, path, .. } => format!("{}: from {}", name, path.display()),
+        };
+        if self.is_sentences() {
+            format!(
+                "{} (sentences, typed with capitals and punctuation)",
+                description
+            )
+        } else {
+            description
+        }
+    }
+}
+
+impl fmt::Display for WordList {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WordList::Pack(pack) => write!(f, "{}", pack.manifest.name),
+            WordList::Sentences(pack) => write!(f, "{} sentences", pack.manifest.name),
+            WordList::Custom {
+                name,
+                sentences: false,
+                ..
+            } => write!(f, "{} (custom)", name),
+            WordList::Custom { name, .. } => write!(f, "{} (sentences)", name),
+        }
+    }
+}
+
+// One word per line, skipping blank lines and words that are already in the list.
+// Words that only differ in case are both kept for strict games, and the engine
+// keeps them off the screen together when case does not count.
+fn parse(data: &str, normalization: Normalization) -> Vec<String> {
+    let mut seen = HashSet::new();
+    data.lines()
+        .map(|line| normalization.apply(line.trim()))
+        .filter(|word| !word.is_empty() && seen.insert(word.clone()))
+        .collect()
+}
+
+// One sentence per line, with its spacing tidied up, skipping sentences that are
+// too wide for the screen or already in the list
+fn parse_sentences(data: &str, normalization: Normalization) -> Vec<String> {
+    let mut seen = HashSet::new();
+    data.lines()
+        .map(|line| normalization.apply(&line.split_whitespace().collect::<Vec<_>>().join(" ")))
+        .filter(|sentence| {
+            !sentence.is_empty()
+                && sentence.width() <= MAX_SENTENCE_WIDTH
+                && seen.insert(sentence.clone())
+        })
+        .collect()
+}
+
+pub(crate) fn config_dir() -> Option<PathBuf> {
+    storage::config_path("wordlists")
+}
+
+pub(crate) fn sentences_config_dir() -> Option<PathBuf> {
+    storage::config_path("sentences")
+}
+
+// Collect the language packs and their sentences, followed by the lists in the
+// config directories and on the command line
+pub(crate) fn discover(extra_paths: &[PathBuf], extra_sentences: bool) -> Vec<WordList> {
+    let mut word_lists = vec![];
+    for pack in language_pack::discover() {
+        if pack.has_sentences() {
+            word_lists.push(WordList::Pack(pack.clone()));
+            word_lists.push(WordList::Sentences(pack));
+        } else {
+            word_lists.push(WordList::Pack(pack));
+        }
+    }
+
+    let paths = text_files(config_dir())
+        .into_iter()
+        .map(|path| (path, false))
+        .chain(
+            text_files(sentences_config_dir())
+                .into_iter()
+                .map(|path| (path, true)),
+        )
+        .chain(
+            extra_paths
+                .iter()
+                .map(|path| (path.clone(), extra_sentences)),
+        );
+    for (path, sentences) in paths {
+        let word_list = WordList::from_path(&path, sentences);
+        if !word_lists.contains(&word_list) {
+            word_lists.push(word_list);
+        }
+    }
+    word_lists
+}
+
+// The *.txt files in a directory, sorted by name
+fn text_files(dir: Option<PathBuf>) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = dir
+        .and_then(|dir| fs::read_dir(dir).ok())
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+                .collect()
+        })
+        .unwrap_or_default();
+    paths.sort();
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn removes_duplicate_words() {
+        assert_eq!(
+            parse(
+                "alpha\n\n beta \nMarch\ngamma\nbeta\nmarch\n",
+                Normalization::Nfc
+            ),
+            vec!["alpha", "beta", "March", "gamma", "march"]
+        );
+        // Full-width letters are folded into ordinary ones by NFKC
+        assert_eq!(
+            parse("ｔｅａ\ntea\ncafe\u{301}\n", Normalization::Nfkc),
+            vec!["tea", "café"]
+        );
+    }
+
+    #[test]
+    fn reads_one_sentence_per_line() {
+        let long = "word ".repeat(20);
+        let data = format!(
+            "The quick brown fox.\n\n  Hello,   world!\n{}\nthe quick brown fox.\nHello, world!\n",
+            long
+        );
+        assert_eq!(
+            parse_sentences(&data, Normalization::Nfc),
+            vec![
+                "The quick brown fox.",
+                "Hello, world!",
+                "the quick brown fox."
+            ]
+        );
+    }
+}